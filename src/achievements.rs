@@ -0,0 +1,350 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::*;
+
+const TOAST_DURATION: f32 = 3.;
+const TOAST_SLIDE_DURATION: f32 = 0.3;
+const TOAST_WIDTH: f32 = 420.;
+const TOAST_HEIGHT: f32 = 96.;
+const TOAST_Z_VALUE: f32 = 110.;
+
+pub struct AchievementsPlugin;
+
+pub enum AchievementCondition {
+    /// Clear a level without losing a ball
+    FlawlessLevel,
+    /// Destroy this many bricks in one combo chain
+    Chain(u32),
+    /// Destroy this many bricks with a single fireball
+    FireballKills(u32),
+    /// Destroy a brick this many bomb explosions deep
+    BombChain(u8),
+    ReachLevel(u16),
+}
+
+pub struct Achievement {
+    pub id: &'static str,
+    /// Locale keys of the texts shown when unlocked
+    pub title: &'static str,
+    pub description: &'static str,
+    pub condition: AchievementCondition,
+}
+
+pub static ACHIEVEMENTS: [Achievement; 5] = [
+    Achievement {
+        id: "flawless",
+        title: "achievement_flawless",
+        description: "achievement_flawless_description",
+        condition: AchievementCondition::FlawlessLevel,
+    },
+    Achievement {
+        id: "chain_20",
+        title: "achievement_chain_20",
+        description: "achievement_chain_20_description",
+        condition: AchievementCondition::Chain(20),
+    },
+    Achievement {
+        id: "inferno",
+        title: "achievement_inferno",
+        description: "achievement_inferno_description",
+        condition: AchievementCondition::FireballKills(5),
+    },
+    Achievement {
+        id: "demolition",
+        title: "achievement_demolition",
+        description: "achievement_demolition_description",
+        condition: AchievementCondition::BombChain(3),
+    },
+    Achievement {
+        id: "level_10",
+        title: "achievement_level_10",
+        description: "achievement_level_10_description",
+        condition: AchievementCondition::ReachLevel(10),
+    },
+];
+
+/// Gameplay facts the achievement conditions are checked against
+#[derive(Resource, Default)]
+pub struct AchievementTracker {
+    pub chain: u32,
+    /// Most bricks destroyed by any one fireball
+    pub fireball_kills: u32,
+    kills_per_fireball: HashMap<Entity, u32>,
+    pub bomb_depth: u8,
+    pub balls_lost: u32,
+    pub level: u16,
+    pub level_cleared: bool,
+}
+
+impl AchievementTracker {
+    pub fn record_fireball_kill(&mut self, fireball: Entity) {
+        let kills = self.kills_per_fireball.entry(fireball).or_insert(0);
+        *kills += 1;
+
+        self.fireball_kills = self.fireball_kills.max(*kills);
+    }
+}
+
+impl AchievementCondition {
+    pub fn is_met(&self, tracker: &AchievementTracker) -> bool {
+        match *self {
+            AchievementCondition::FlawlessLevel => tracker.level_cleared && tracker.balls_lost == 0,
+            AchievementCondition::Chain(length) => tracker.chain >= length,
+            AchievementCondition::FireballKills(count) => tracker.fireball_kills >= count,
+            AchievementCondition::BombChain(depth) => tracker.bomb_depth >= depth,
+            AchievementCondition::ReachLevel(level) => tracker.level >= level,
+        }
+    }
+}
+
+/// Ids of the achievements the current profile unlocked in any session
+#[derive(Resource, Default)]
+pub struct UnlockedAchievements(pub Vec<&'static str>);
+
+impl UnlockedAchievements {
+    pub fn parse(source: &str) -> Self {
+        UnlockedAchievements(
+            ACHIEVEMENTS
+                .iter()
+                .map(|achievement| achievement.id)
+                .filter(|id| source.lines().any(|line| line.trim() == *id))
+                .collect(),
+        )
+    }
+
+    pub fn serialize(&self) -> String {
+        self.0.join("\n")
+    }
+}
+
+pub struct AchievementUnlockedEvent(pub &'static Achievement);
+
+/// Unlocked achievements waiting to be announced
+#[derive(Resource, Default)]
+pub struct AchievementToasts(VecDeque<&'static Achievement>);
+
+#[derive(Component)]
+pub struct AchievementToast {
+    age: f32,
+}
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AchievementTracker::default())
+            .insert_resource(AchievementToasts::default())
+            .add_event::<AchievementUnlockedEvent>();
+
+        app.add_systems(
+            (
+                reset_achievement_tracker.before(track_achievements),
+                track_achievements.after(update_score).after(next_level),
+                check_achievements.after(track_achievements),
+            )
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems((queue_achievement_toasts, show_achievement_toasts).chain());
+    }
+}
+
+fn reset_achievement_tracker(
+    mut tracker: ResMut<AchievementTracker>,
+    mut started_events: EventReader<LevelStartedEvent>,
+) {
+    for event in started_events.iter() {
+        *tracker = AchievementTracker {
+            level: event.level,
+            ..default()
+        };
+    }
+}
+
+fn track_achievements(
+    mut tracker: ResMut<AchievementTracker>,
+    mut destruction_events: EventReader<BrickDesctructionEvent>,
+    mut life_lost_events: EventReader<LifeLostEvent>,
+    mut cleared_events: EventReader<LevelClearedEvent>,
+    combo: Res<ComboChain>,
+    playtest: Res<EditorPlaytest>,
+) {
+    if playtest.0 {
+        return;
+    }
+
+    tracker.chain = combo.count;
+
+    for event in destruction_events.iter() {
+        tracker.bomb_depth = tracker.bomb_depth.max(event.chain_depth);
+
+        // Bombs set off by a fireball count towards its kills
+        if event.ball_type == BallType::FireBall {
+            tracker.record_fireball_kill(event.ball);
+        }
+    }
+
+    tracker.balls_lost += life_lost_events.iter().count() as u32;
+    for event in cleared_events.iter() {
+        tracker.level_cleared |= event.level == tracker.level;
+    }
+}
+
+fn check_achievements(
+    tracker: Res<AchievementTracker>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+    mut unlock_events: EventWriter<AchievementUnlockedEvent>,
+    profiles: Res<Profiles>,
+    playtest: Res<EditorPlaytest>,
+) {
+    // Hand-made test levels must not unlock achievements on the profile
+    if playtest.0 {
+        return;
+    }
+
+    let mut changed = false;
+
+    for achievement in ACHIEVEMENTS.iter() {
+        if !unlocked.0.contains(&achievement.id) && achievement.condition.is_met(&tracker) {
+            unlocked.0.push(achievement.id);
+            unlock_events.send(AchievementUnlockedEvent(achievement));
+            changed = true;
+        }
+    }
+
+    if changed {
+        if let Err(error) = write_save(&profiles.save_name("achievements"), &unlocked.serialize()) {
+            warn!("Could not save achievements: {}", error);
+        }
+    }
+}
+
+fn queue_achievement_toasts(
+    mut unlock_events: EventReader<AchievementUnlockedEvent>,
+    mut toasts: ResMut<AchievementToasts>,
+) {
+    for event in unlock_events.iter() {
+        toasts.0.push_back(event.0);
+    }
+}
+
+fn toast_position(age: f32) -> Vec3 {
+    let shown = WIN_HEIGHT / 2. - TOAST_HEIGHT;
+    let hidden = WIN_HEIGHT / 2. + TOAST_HEIGHT;
+    let slide = (age / TOAST_SLIDE_DURATION)
+        .min((TOAST_DURATION - age) / TOAST_SLIDE_DURATION)
+        .clamp(0., 1.);
+
+    Vec3::new(0., hidden + (shown - hidden) * slide, TOAST_Z_VALUE)
+}
+
+fn show_achievement_toasts(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut toasts: ResMut<AchievementToasts>,
+    mut query: Query<(Entity, &mut AchievementToast, &mut Transform)>,
+    locale: Res<Locale>,
+    time: Res<Time>,
+) {
+    if query.is_empty() {
+        if let Some(achievement) = toasts.0.pop_front() {
+            commands
+                .spawn((
+                    Text2dBundle {
+                        text: Text::from_sections([
+                            TextSection::new(
+                                format!("{}\n", locale.get("achievement_unlocked")),
+                                TextStyle {
+                                    font: assets.font.bold.clone(),
+                                    font_size: 20.0,
+                                    color: Color::GOLD,
+                                },
+                            ),
+                            TextSection::new(
+                                format!("{}\n", locale.get(achievement.title)),
+                                TextStyle {
+                                    font: assets.font.bold.clone(),
+                                    font_size: 32.0,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                            TextSection::new(
+                                locale.get(achievement.description),
+                                TextStyle {
+                                    font: assets.font.bold.clone(),
+                                    font_size: 18.0,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                        ])
+                        .with_alignment(TextAlignment::Center),
+                        transform: Transform::from_translation(toast_position(0.)),
+                        ..default()
+                    },
+                    AchievementToast { age: 0. },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(0., 0., 0., 0.7),
+                            custom_size: Some(Vec2::new(TOAST_WIDTH, TOAST_HEIGHT)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0., 0., -1.),
+                        ..default()
+                    });
+                });
+        }
+    }
+
+    // Toasts run on real time so they keep moving during hit-stop
+    for (entity, mut toast, mut transform) in query.iter_mut() {
+        toast.age += time.raw_delta_seconds();
+
+        if toast.age >= TOAST_DURATION {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            transform.translation = toast_position(toast.age);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flawless_needs_a_cleared_level_without_losses() {
+        let mut tracker = AchievementTracker::default();
+
+        assert!(!AchievementCondition::FlawlessLevel.is_met(&tracker));
+        tracker.level_cleared = true;
+        assert!(AchievementCondition::FlawlessLevel.is_met(&tracker));
+        tracker.balls_lost = 1;
+        assert!(!AchievementCondition::FlawlessLevel.is_met(&tracker));
+    }
+
+    #[test]
+    fn fireball_kills_count_per_fireball() {
+        let mut tracker = AchievementTracker::default();
+        let (first, second) = (Entity::from_raw(1), Entity::from_raw(2));
+
+        for _ in 0..3 {
+            tracker.record_fireball_kill(first);
+        }
+        tracker.record_fireball_kill(second);
+        tracker.record_fireball_kill(second);
+
+        assert_eq!(tracker.fireball_kills, 3);
+        assert!(!AchievementCondition::FireballKills(4).is_met(&tracker));
+        tracker.record_fireball_kill(first);
+        assert!(AchievementCondition::FireballKills(4).is_met(&tracker));
+    }
+
+    #[test]
+    fn unlocked_achievements_round_trip() {
+        let unlocked = UnlockedAchievements(vec!["inferno", "flawless"]);
+        let parsed = UnlockedAchievements::parse(&unlocked.serialize());
+
+        assert_eq!(parsed.0.len(), 2);
+        assert!(parsed.0.contains(&"inferno"));
+        assert!(UnlockedAchievements::parse("unknown\n").0.is_empty());
+    }
+}
@@ -0,0 +1,460 @@
+use bevy::render::render_resource::{TextureDimension, TextureFormat};
+
+use crate::*;
+
+const CRACK_FRAMES: usize = 3;
+const CRACK_FPS: f32 = 24.;
+const FLICKER_LIGHTNESS: [f32; 4] = [0., 0.3, 0.1, 0.4];
+const FLICKER_FPS: f32 = 12.;
+const PULSE_FRAMES: usize = 8;
+const PULSE_FPS: f32 = 10.;
+/// Darkest shade of the greyscale bricks, keeps them bright enough to tint
+const GREYSCALE_FLOOR: f32 = 0.35;
+
+pub struct AnimationPlugin;
+
+/// Atlas indices of the frames of a sprite, the first one is the resting frame
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Frames(pub Vec<usize>);
+
+impl Frames {
+    /// Resting frame, an image that failed to load has no frames and shows the first atlas entry
+    pub fn first(&self) -> usize {
+        self.0.first().copied().unwrap_or(0)
+    }
+}
+
+/// Every game sprite packed into one atlas, built from the theme images once they are loaded
+#[derive(Resource)]
+pub struct SpriteSheet {
+    pub atlas: Handle<TextureAtlas>,
+    pub ball: Frames,
+    /// Flicker frames
+    pub ball_fire: Frames,
+    /// Pulse frames, played while a power-up is active
+    pub paddle: Frames,
+    /// Intact brick followed by its crack frames, in `BrickColor::ALL` order
+    bricks: Vec<Frames>,
+    bomb: Frames,
+    /// Greyscale brick and bomb with crack frames, palettes tint them through the sprite colour
+    grey_brick: Frames,
+    grey_bomb: Frames,
+}
+
+impl SpriteSheet {
+    pub fn brick(&self, brick_type: BrickType, color: BrickColor) -> &Frames {
+        if brick_type == BrickType::Bomb {
+            return &self.bomb;
+        }
+
+        let index = BrickColor::ALL
+            .iter()
+            .position(|c| *c == color)
+            .unwrap_or(0);
+
+        &self.bricks[index]
+    }
+
+    pub fn grey_brick(&self, brick_type: BrickType) -> &Frames {
+        match brick_type {
+            BrickType::Bomb => &self.grey_bomb,
+            _ => &self.grey_brick,
+        }
+    }
+
+    /// Frames of the brick showing this atlas index, standard or greyscale
+    pub fn brick_frames(&self, brick_type: BrickType, color: BrickColor, index: usize) -> &Frames {
+        let grey = self.grey_brick(brick_type);
+
+        if grey.0.contains(&index) {
+            grey
+        } else {
+            self.brick(brick_type, color)
+        }
+    }
+
+    /// Sprite showing the resting frame
+    pub fn bundle(&self, frames: &Frames, transform: Transform) -> SpriteSheetBundle {
+        SpriteSheetBundle {
+            sprite: TextureAtlasSprite::new(frames.first()),
+            texture_atlas: self.atlas.clone(),
+            transform,
+            ..default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AnimationMode {
+    Loop,
+    /// Removes the entity after the last frame
+    DespawnWhenDone,
+}
+
+#[derive(Component)]
+pub struct SpriteAnimation {
+    pub frames: Frames,
+    pub fps: f32,
+    pub mode: AnimationMode,
+    elapsed: f32,
+}
+
+impl SpriteAnimation {
+    pub fn new(frames: Frames, fps: f32, mode: AnimationMode) -> Self {
+        SpriteAnimation {
+            frames,
+            fps,
+            mode,
+            elapsed: 0.,
+        }
+    }
+
+    pub fn flicker(frames: &Frames) -> Self {
+        SpriteAnimation::new(frames.clone(), FLICKER_FPS, AnimationMode::Loop)
+    }
+
+    /// Position in the frame list, none once a despawning animation is over
+    pub fn frame(&self) -> Option<usize> {
+        let len = self.frames.0.len();
+        let frame = (self.elapsed * self.fps) as usize;
+
+        match self.mode {
+            AnimationMode::Loop => Some(frame.checked_rem(len).unwrap_or(0)),
+            AnimationMode::DespawnWhenDone => (frame < len).then_some(frame),
+        }
+    }
+}
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(build_sprite_sheet.in_schedule(OnExit(GameState::Loading)))
+            .add_system(animate_sprites)
+            .add_systems(
+                // Runs while the destroyed bricks are still there to look at
+                (crack_destroyed_bricks.after(check_collisions), pulse_paddle)
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+    }
+}
+
+/// Darkens pixels along jagged lines from the centre, longer for later stages
+pub fn crack(data: &mut [u8], width: usize, height: usize, stage: usize, stages: usize) {
+    const RAYS: [(f32, f32); 4] = [(0.4, 0.9), (2.0, -0.7), (3.5, 0.6), (4.9, -1.1)];
+
+    let center = Vec2::new(width as f32, height as f32) / 2.;
+    let length = center.length() * stage as f32 / stages as f32;
+
+    for (angle, jag) in RAYS {
+        let direction = Vec2::from_angle(angle);
+        let side = direction.perp();
+
+        for step in 0..length as usize {
+            // Zigzag sideways every few pixels
+            let offset = if (step / 4) % 2 == 0 { jag } else { -jag };
+            let point = center + direction * step as f32 + side * offset;
+
+            if point.x < 0. || point.y < 0. {
+                continue;
+            }
+
+            let (x, y) = (point.x as usize, point.y as usize);
+
+            if x < width && y < height {
+                let i = (y * width + x) * 4;
+
+                for channel in &mut data[i..i + 3] {
+                    *channel = (*channel as f32 * 0.3) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Blends the colour of every pixel towards white, keeping its alpha
+pub fn lighten(data: &mut [u8], amount: f32) {
+    for pixel in data.chunks_exact_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel = (*channel as f32 + (255. - *channel as f32) * amount) as u8;
+        }
+    }
+}
+
+/// Turns every pixel into a light grey of its brightness, keeping its alpha
+pub fn greyscale(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|channel| channel as f32 / 255.);
+        let luminance = 0.3 * r + 0.6 * g + 0.1 * b;
+        let grey = ((GREYSCALE_FLOOR + (1. - GREYSCALE_FLOOR) * luminance) * 255.) as u8;
+
+        pixel[..3].fill(grey);
+    }
+}
+
+/// Adds copies of an image to the atlas, each changed by `edit` given the pixels, size and frame
+fn add_frames(
+    images: &mut Assets<Image>,
+    builder: &mut TextureAtlasBuilder,
+    source: &Handle<Image>,
+    edit: impl Fn(&mut [u8], usize, usize, usize),
+    count: usize,
+) -> Vec<Handle<Image>> {
+    let Some(image) = images
+        .get(source)
+        .and_then(|image| image.convert(TextureFormat::Rgba8UnormSrgb))
+    else {
+        return vec![];
+    };
+
+    let size = image.texture_descriptor.size;
+
+    (0..count)
+        .map(|frame| {
+            let mut data = image.data.clone();
+            edit(&mut data, size.width as usize, size.height as usize, frame);
+
+            // Only the atlas keeps the pixels, the frame images go once the handles drop
+            let handle = images.add(Image::new(
+                size,
+                TextureDimension::D2,
+                data,
+                TextureFormat::Rgba8UnormSrgb,
+            ));
+            builder.add_texture(handle.clone(), images.get(&handle).unwrap());
+
+            handle
+        })
+        .collect()
+}
+
+/// Packs the theme images and frames derived from them into the sprite sheet
+fn build_sprite_sheet(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let mut builder = TextureAtlasBuilder::default();
+
+    let cracked =
+        |data: &mut [u8], width, height, frame| crack(data, width, height, frame, CRACK_FRAMES);
+    let flickering = |data: &mut [u8], _, _, frame| lighten(data, FLICKER_LIGHTNESS[frame]);
+    let pulsing = |data: &mut [u8], _, _, frame| {
+        lighten(
+            data,
+            0.35 * (std::f32::consts::PI * frame as f32 / PULSE_FRAMES as f32).sin(),
+        )
+    };
+    let grey_cracked = |data: &mut [u8], width, height, frame| {
+        greyscale(data);
+        crack(data, width, height, frame, CRACK_FRAMES);
+    };
+    let unchanged = |_: &mut [u8], _, _, _| {};
+
+    let image = &assets.image;
+    let ball = add_frames(&mut images, &mut builder, &image.ball, unchanged, 1);
+    let ball_fire = add_frames(
+        &mut images,
+        &mut builder,
+        &image.ball_fire,
+        flickering,
+        FLICKER_LIGHTNESS.len(),
+    );
+    let paddle = add_frames(
+        &mut images,
+        &mut builder,
+        &image.paddle,
+        pulsing,
+        PULSE_FRAMES,
+    );
+    let bomb = add_frames(
+        &mut images,
+        &mut builder,
+        &image.brick_magenta,
+        cracked,
+        CRACK_FRAMES + 1,
+    );
+    let bricks: Vec<_> = BrickColor::ALL
+        .iter()
+        .map(|color| {
+            add_frames(
+                &mut images,
+                &mut builder,
+                &color.texture(image),
+                cracked,
+                CRACK_FRAMES + 1,
+            )
+        })
+        .collect();
+
+    let grey_brick = add_frames(
+        &mut images,
+        &mut builder,
+        &image.brick_yellow,
+        grey_cracked,
+        CRACK_FRAMES + 1,
+    );
+    let grey_bomb = add_frames(
+        &mut images,
+        &mut builder,
+        &image.brick_magenta,
+        grey_cracked,
+        CRACK_FRAMES + 1,
+    );
+
+    let atlas = match builder.finish(&mut images) {
+        Ok(atlas) => atlas,
+        Err(error) => {
+            error!("Could not build the sprite sheet: {}", error);
+            return;
+        }
+    };
+
+    let frames = |handles: &[Handle<Image>]| {
+        Frames(
+            handles
+                .iter()
+                .filter_map(|handle| atlas.get_texture_index(handle))
+                .collect(),
+        )
+    };
+
+    let sheet = SpriteSheet {
+        ball: frames(&ball),
+        ball_fire: frames(&ball_fire),
+        paddle: frames(&paddle),
+        bricks: bricks.iter().map(|handles| frames(handles)).collect(),
+        bomb: frames(&bomb),
+        grey_brick: frames(&grey_brick),
+        grey_bomb: frames(&grey_bomb),
+        atlas: atlases.add(atlas),
+    };
+
+    commands.insert_resource(sheet);
+}
+
+fn animate_sprites(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut SpriteAnimation, &mut TextureAtlasSprite)>,
+) {
+    for (entity, mut animation, mut sprite) in &mut query {
+        animation.elapsed += time.delta_seconds();
+
+        match animation.frame() {
+            Some(frame) => {
+                if let Some(index) = animation.frames.0.get(frame) {
+                    sprite.index = *index;
+                }
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
+
+/// Leaves a cracking copy of every destroyed brick behind
+fn crack_destroyed_bricks(
+    mut commands: Commands,
+    mut events: EventReader<BrickDesctructionEvent>,
+    sheet: Option<Res<SpriteSheet>>,
+    bricks: Query<&TextureAtlasSprite, With<Brick>>,
+) {
+    let Some(sheet) = sheet else {
+        return;
+    };
+
+    for event in events.iter() {
+        let Ok(brick_sprite) = bricks.get(event.brick) else {
+            continue;
+        };
+
+        // Same frames and tint as the brick, so palette bricks crack in their palette colour
+        let frames = sheet.brick_frames(event.brick_type, event.color, brick_sprite.index);
+        let mut bundle = sheet.bundle(frames, Transform::from_translation(event.position));
+        bundle.sprite.color = brick_sprite.color;
+
+        commands.spawn((
+            bundle,
+            SpriteAnimation::new(frames.clone(), CRACK_FPS, AnimationMode::DespawnWhenDone),
+        ));
+    }
+}
+
+/// Pulses the paddle while a fireball is out
+fn pulse_paddle(
+    mut commands: Commands,
+    sheet: Option<Res<SpriteSheet>>,
+    fireballs: Query<(), With<FireBall>>,
+    mut paddles: Query<(Entity, &mut TextureAtlasSprite, Option<&SpriteAnimation>), With<Paddle>>,
+) {
+    let Some(sheet) = sheet else {
+        return;
+    };
+
+    for (entity, mut sprite, animation) in &mut paddles {
+        match (fireballs.is_empty(), animation.is_some()) {
+            (false, false) => {
+                commands.entity(entity).insert(SpriteAnimation::new(
+                    sheet.paddle.clone(),
+                    PULSE_FPS,
+                    AnimationMode::Loop,
+                ));
+            }
+            (true, true) => {
+                commands.entity(entity).remove::<SpriteAnimation>();
+                sprite.index = sheet.paddle.first();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animation_modes_end_differently() {
+        let mut animation = SpriteAnimation::new(Frames(vec![4, 5, 6]), 10., AnimationMode::Loop);
+        animation.elapsed = 0.35;
+        assert_eq!(animation.frame(), Some(0));
+
+        animation.mode = AnimationMode::DespawnWhenDone;
+        assert_eq!(animation.frame(), None);
+        animation.elapsed = 0.15;
+        assert_eq!(animation.frame(), Some(1));
+    }
+
+    #[test]
+    fn missing_frames_fall_back_to_the_first_atlas_entry() {
+        let frames = Frames::default();
+        let animation = SpriteAnimation::new(frames.clone(), 10., AnimationMode::Loop);
+
+        assert_eq!(frames.first(), 0);
+        assert_eq!(animation.frame(), Some(0));
+    }
+
+    #[test]
+    fn greyscale_keeps_brightness_order_and_alpha() {
+        let mut data = vec![255, 220, 0, 255, 20, 40, 160, 128];
+        greyscale(&mut data);
+
+        assert_eq!(data[0], data[2]);
+        assert!(data[0] > data[4]);
+        assert!(data[4] as f32 >= GREYSCALE_FLOOR * 255. - 1.);
+        assert_eq!(data[7], 128);
+    }
+
+    #[test]
+    fn later_crack_stages_darken_more_pixels() {
+        let (width, height) = (64, 32);
+        let dark_pixels = |stage| {
+            let mut data = vec![200; width * height * 4];
+            crack(&mut data, width, height, stage, CRACK_FRAMES);
+            data.chunks_exact(4).filter(|pixel| pixel[0] < 200).count()
+        };
+
+        assert_eq!(dark_pixels(0), 0);
+        assert!(dark_pixels(1) > 0);
+        assert!(dark_pixels(CRACK_FRAMES) > dark_pixels(1));
+    }
+}
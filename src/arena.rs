@@ -0,0 +1,211 @@
+use crate::*;
+
+/// Walls reach this far outside the arena so fast balls cannot pass through them
+const WALL_THICKNESS: f32 = 200.;
+const WALL_COLOR: Color = Color::rgba(0., 0., 0., 0.5);
+const PADDLE_SHRINK_FACTOR: f32 = 0.5;
+
+pub struct ArenaPlugin;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WallSide {
+    Left,
+    Right,
+    Ceiling,
+}
+
+impl WallSide {
+    /// Points from the wall into the arena
+    pub fn normal(&self) -> Vec2 {
+        match self {
+            WallSide::Left => Vec2::X,
+            WallSide::Right => Vec2::NEG_X,
+            WallSide::Ceiling => Vec2::NEG_Y,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Wall {
+    pub side: WallSide,
+}
+
+/// Inner edges of the walls, levels may close the arena in from the window edges
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ArenaBounds {
+    pub left: f32,
+    pub right: f32,
+    pub ceiling: f32,
+}
+
+impl Default for ArenaBounds {
+    fn default() -> Self {
+        ArenaBounds {
+            left: -WIN_WIDTH / 2.,
+            right: WIN_WIDTH / 2.,
+            ceiling: WIN_HEIGHT / 2.,
+        }
+    }
+}
+
+impl ArenaBounds {
+    /// Parses `<left> <right> <ceiling>`, the arena must stay wider than the paddle
+    pub fn parse(value: &str) -> Option<Self> {
+        let parts: Vec<f32> = value
+            .split_whitespace()
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?;
+        let [left, right, ceiling] = parts[..] else {
+            return None;
+        };
+
+        (right - left > PADDLE_WIDTH && ceiling > PADDLE_Y).then_some(ArenaBounds {
+            left,
+            right,
+            ceiling,
+        })
+    }
+
+    pub fn serialize(&self) -> String {
+        format!("{} {} {}", self.left, self.right, self.ceiling)
+    }
+
+    /// Whether a brick in the grid cell lies fully inside the arena, where the ball can reach it
+    pub fn holds_cell(&self, x: usize, y: usize) -> bool {
+        let position = brick_position(x, y);
+
+        position.x - BRICK_WIDTH / 2. >= self.left
+            && position.x + BRICK_WIDTH / 2. <= self.right
+            && position.y + BRICK_HEIGHT / 2. <= self.ceiling
+    }
+
+    /// Centre and size of the wall on a side
+    fn wall(&self, side: WallSide) -> (Vec2, Vec2) {
+        let height = WIN_HEIGHT + WALL_THICKNESS * 2.;
+        let width = self.right - self.left + WALL_THICKNESS * 2.;
+
+        match side {
+            WallSide::Left => (
+                Vec2::new(self.left - WALL_THICKNESS / 2., 0.),
+                Vec2::new(WALL_THICKNESS, height),
+            ),
+            WallSide::Right => (
+                Vec2::new(self.right + WALL_THICKNESS / 2., 0.),
+                Vec2::new(WALL_THICKNESS, height),
+            ),
+            WallSide::Ceiling => (
+                Vec2::new(
+                    (self.left + self.right) / 2.,
+                    self.ceiling + WALL_THICKNESS / 2.,
+                ),
+                Vec2::new(width, WALL_THICKNESS),
+            ),
+        }
+    }
+}
+
+/// Whether the paddle of the current level has been shrunk by a ceiling hit
+#[derive(Resource, Default)]
+pub struct PaddleShrunk(pub bool);
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PaddleShrunk::default())
+            .add_system(
+                spawn_walls
+                    .after(load_level)
+                    .in_schedule(OnEnter(GameState::Playing)),
+            )
+            .add_system(shrink_paddle_on_ceiling_hit.in_set(OnUpdate(GameState::Playing)))
+            .add_system(despawn::<Wall>.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+fn spawn_walls(
+    mut commands: Commands,
+    level: Res<CurrentLevel>,
+    mut paddle_shrunk: ResMut<PaddleShrunk>,
+) {
+    paddle_shrunk.0 = false;
+
+    for side in [WallSide::Left, WallSide::Right, WallSide::Ceiling] {
+        let (position, size) = level.0.bounds.wall(side);
+
+        // Walls on the window edges are out of sight, custom ones show where the arena ends
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: WALL_COLOR,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(5.)),
+                ..default()
+            },
+            Wall { side },
+            Collider { size },
+        ));
+    }
+}
+
+/// Classic rule, the paddle halves once a ball first reaches the ceiling
+fn shrink_paddle_on_ceiling_hit(
+    mut collision_events: EventReader<BallCollisionEvent>,
+    mut paddle_shrunk: ResMut<PaddleShrunk>,
+    level: Res<CurrentLevel>,
+    mut paddle_query: Query<(&mut Transform, &mut Collider), With<Paddle>>,
+) {
+    let ceiling_hit = collision_events
+        .iter()
+        .any(|event| event.collision_type == BallCollisionType::Wall(WallSide::Ceiling));
+
+    if !ceiling_hit || !level.0.rules.shrink_paddle || paddle_shrunk.0 {
+        return;
+    }
+
+    paddle_shrunk.0 = true;
+
+    for (mut transform, mut collider) in &mut paddle_query {
+        transform.scale.x *= PADDLE_SHRINK_FACTOR;
+        collider.size.x *= PADDLE_SHRINK_FACTOR;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_round_trip_and_reject_narrow_arenas() {
+        let bounds = ArenaBounds::parse("-250 250 300").unwrap();
+
+        assert_eq!(bounds.right, 250.);
+        assert_eq!(ArenaBounds::parse(&bounds.serialize()), Some(bounds));
+        assert_eq!(ArenaBounds::parse("-10 10 300"), None);
+        assert_eq!(ArenaBounds::parse("-250 250"), None);
+    }
+
+    #[test]
+    fn walls_sit_outside_the_arena() {
+        let bounds = ArenaBounds::default();
+        let (position, size) = bounds.wall(WallSide::Left);
+
+        assert_eq!(position.x + size.x / 2., bounds.left);
+        assert_eq!(WallSide::Left.normal(), Vec2::X);
+
+        let (position, size) = bounds.wall(WallSide::Ceiling);
+        assert_eq!(position.y - size.y / 2., bounds.ceiling);
+    }
+
+    #[test]
+    fn levels_cannot_hide_bricks_behind_walls() {
+        let narrow = ArenaBounds::parse("-256 256 380").unwrap();
+
+        assert!(!narrow.holds_cell(0, 0));
+        assert!(narrow.holds_cell(1, 0));
+        assert!(Level::parse("bounds: -256 256 380\nr........r").is_err());
+        assert!(Level::parse("bounds: -256 256 380\n.rrrrrrrr.").is_ok());
+        assert!(Level::parse("bounds: -336 336 380\nrrrrrrrrrr").is_ok());
+        assert!(Level::parse("bounds: -336 336 300\nrrrrrrrrrr").is_err());
+    }
+}
@@ -1,17 +1,37 @@
-use bevy::prelude::*;
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::*;
+
+const LOADING_BAR_WIDTH: f32 = 400.;
+const LOADING_BAR_HEIGHT: f32 = 16.;
+const LOADING_BAR_COLOR: Color = Color::rgb(1., 0.84, 0.);
+const LOADING_FAILED_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
 
 pub struct GameAssetsPlugin;
 
 impl Plugin for GameAssetsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(asset_loading.in_base_set(CoreSet::First));
+        app.add_startup_system(asset_loading.in_base_set(CoreSet::First))
+            .add_startup_system(add_fallback_font)
+            .add_system(spawn_loading_screen.in_schedule(OnEnter(GameState::Loading)))
+            .add_system(track_asset_loading.in_set(OnUpdate(GameState::Loading)))
+            .add_system(despawn::<LoadingScreen>.in_schedule(OnExit(GameState::Loading)));
     }
 }
+/// Variations of each sound, one is picked at random every time
 pub struct AudioHandles {
-    pub drop_002: Handle<AudioSource>,
-    pub drop_003: Handle<AudioSource>,
-    pub drop_004: Handle<AudioSource>,
-    pub music_01: Handle<AudioSource>,
+    pub paddle_hit: Vec<Handle<AudioSource>>,
+    pub wall_hit: Vec<Handle<AudioSource>>,
+    pub brick_break: Vec<Handle<AudioSource>>,
+    pub fireball_break: Vec<Handle<AudioSource>>,
+    pub bomb_break: Vec<Handle<AudioSource>>,
+    pub serve: Vec<Handle<AudioSource>>,
+    pub ball_lost: Vec<Handle<AudioSource>>,
+    pub life_lost: Vec<Handle<AudioSource>>,
+    pub fireball_spawn: Vec<Handle<AudioSource>>,
+    pub fireball_expire: Vec<Handle<AudioSource>>,
+    pub level_complete: Vec<Handle<AudioSource>>,
+    pub game_over: Vec<Handle<AudioSource>>,
 }
 pub struct ImageHandles {
     pub ball: Handle<Image>,
@@ -23,35 +43,265 @@ pub struct ImageHandles {
     pub brick_yellow: Handle<Image>,
     pub brick_orange: Handle<Image>,
     pub brick_cyan: Handle<Image>,
+    pub brick_magenta: Handle<Image>,
     pub brick_light_green: Handle<Image>,
     pub background: Handle<Image>,
 }
+pub struct FontHandles {
+    pub bold: Handle<Font>,
+    pub mono: Handle<Font>,
+}
 #[derive(Resource)]
 pub struct GameAssets {
     pub audio: AudioHandles,
     pub image: ImageHandles,
+    pub font: FontHandles,
+}
+
+impl GameAssets {
+    /// Starts loading every asset of the selected theme
+    pub fn load(asset_server: &AssetServer, themes: &Themes) -> Self {
+        let load_all = |key: &str| {
+            themes
+                .paths(key)
+                .into_iter()
+                .map(|path| asset_server.load(path))
+                .collect()
+        };
+
+        GameAssets {
+            audio: AudioHandles {
+                paddle_hit: load_all("paddle_hit"),
+                wall_hit: load_all("wall_hit"),
+                brick_break: load_all("brick_break"),
+                fireball_break: load_all("fireball_break"),
+                bomb_break: load_all("bomb_break"),
+                serve: load_all("serve"),
+                ball_lost: load_all("ball_lost"),
+                life_lost: load_all("life_lost"),
+                fireball_spawn: load_all("fireball_spawn"),
+                fireball_expire: load_all("fireball_expire"),
+                level_complete: load_all("level_complete"),
+                game_over: load_all("game_over"),
+            },
+            image: ImageHandles {
+                ball: asset_server.load(themes.path("ball")),
+                ball_fire: asset_server.load(themes.path("ball_fire")),
+                paddle: asset_server.load(themes.path("paddle")),
+                brick_blue: asset_server.load(themes.path("brick_blue")),
+                brick_green: asset_server.load(themes.path("brick_green")),
+                brick_red: asset_server.load(themes.path("brick_red")),
+                brick_yellow: asset_server.load(themes.path("brick_yellow")),
+                brick_orange: asset_server.load(themes.path("brick_orange")),
+                brick_cyan: asset_server.load(themes.path("brick_cyan")),
+                brick_magenta: asset_server.load(themes.path("brick_magenta")),
+                brick_light_green: asset_server.load(themes.path("brick_light_green")),
+                background: asset_server.load(themes.path("background")),
+            },
+            font: FontHandles {
+                bold: asset_server.load(themes.path("font_bold")),
+                mono: asset_server.load(themes.path("font_mono")),
+            },
+        }
+    }
+
+    /// Every handle the game needs before it can start
+    pub fn handles(&self) -> Vec<HandleUntyped> {
+        let (audio, image, font) = (&self.audio, &self.image, &self.font);
+
+        let sounds = [
+            &audio.paddle_hit,
+            &audio.wall_hit,
+            &audio.brick_break,
+            &audio.fireball_break,
+            &audio.bomb_break,
+            &audio.serve,
+            &audio.ball_lost,
+            &audio.life_lost,
+            &audio.fireball_spawn,
+            &audio.fireball_expire,
+            &audio.level_complete,
+            &audio.game_over,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|handle| handle.clone_untyped());
+
+        sounds
+            .chain([
+                image.ball.clone_untyped(),
+                image.ball_fire.clone_untyped(),
+                image.paddle.clone_untyped(),
+                image.brick_blue.clone_untyped(),
+                image.brick_green.clone_untyped(),
+                image.brick_red.clone_untyped(),
+                image.brick_yellow.clone_untyped(),
+                image.brick_orange.clone_untyped(),
+                image.brick_cyan.clone_untyped(),
+                image.brick_magenta.clone_untyped(),
+                image.brick_light_green.clone_untyped(),
+                image.background.clone_untyped(),
+                font.bold.clone_untyped(),
+                font.mono.clone_untyped(),
+            ])
+            .collect()
+    }
+}
+
+/// Built into the binary so the error screen can still be read when the theme fonts fail
+#[derive(Resource)]
+pub struct FallbackFont(pub Handle<Font>);
+
+#[derive(Component)]
+pub struct LoadingScreen;
+
+#[derive(Component)]
+pub struct LoadingBar;
+
+#[derive(Component)]
+pub struct LoadingErrorText;
+
+fn asset_loading(mut commands: Commands, asset_server: Res<AssetServer>, themes: Res<Themes>) {
+    commands.insert_resource(GameAssets::load(&asset_server, &themes));
+}
+
+fn add_fallback_font(mut commands: Commands, mut fonts: ResMut<Assets<Font>>) {
+    let font = Font::try_from_bytes(include_bytes!("../assets/fonts/FiraMono-Medium.ttf").to_vec())
+        .expect("the embedded fallback font is valid");
+
+    commands.insert_resource(FallbackFont(fonts.add(font)));
 }
 
-fn asset_loading(mut commands: Commands, assets: Res<AssetServer>) {
-    commands.insert_resource(GameAssets {
-        audio: AudioHandles {
-            drop_002: assets.load("sound/drop_002.ogg"),
-            drop_003: assets.load("sound/drop_003.ogg"),
-            drop_004: assets.load("sound/drop_004.ogg"),
-            music_01: assets.load("sound/music_01.mp3"),
-        },
-        image: ImageHandles {
-            ball: assets.load("images/ball.png"),
-            ball_fire: assets.load("images/ball_fire.png"),
-            paddle: assets.load("images/paddle.png"),
-            brick_blue: assets.load("images/brick_blue.png"),
-            brick_green: assets.load("images/brick_green.png"),
-            brick_red: assets.load("images/brick_red.png"),
-            brick_yellow: assets.load("images/brick_yellow.png"),
-            brick_orange: assets.load("images/brick_orange.png"),
-            brick_cyan: assets.load("images/brick_cyan.png"),
-            brick_light_green: assets.load("images/brick_light_green.png"),
-            background: assets.load("images/background.jpeg"),
-        },
-    });
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::new(Val::Px(0.), Val::Px(30.)),
+                    ..default()
+                },
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(LOADING_BAR_WIDTH), Val::Px(LOADING_BAR_HEIGHT)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: LOADING_BAR_COLOR.into(),
+                            ..default()
+                        },
+                        LoadingBar,
+                    ));
+                });
+        });
+}
+
+/// Fills the loading bar, starts the game once everything is in and lists the files that failed
+#[allow(clippy::too_many_arguments)]
+fn track_asset_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
+    themes: Res<Themes>,
+    locale: Res<Locale>,
+    fallback_font: Res<FallbackFont>,
+    mut reload: ResMut<ThemeReload>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut bar_query: Query<(&mut Style, &mut BackgroundColor), With<LoadingBar>>,
+    screen_query: Query<Entity, With<LoadingScreen>>,
+    error_query: Query<(), With<LoadingErrorText>>,
+) {
+    if !error_query.is_empty() {
+        return;
+    }
+
+    let handles = assets.handles();
+    let states: Vec<LoadState> = handles
+        .iter()
+        .map(|handle| asset_server.get_load_state(handle))
+        .collect();
+
+    let loaded = states
+        .iter()
+        .filter(|state| **state == LoadState::Loaded)
+        .count();
+
+    for (mut style, _) in &mut bar_query {
+        style.size.width = Val::Percent(100. * loaded as f32 / handles.len() as f32);
+    }
+
+    let failed: Vec<String> = handles
+        .iter()
+        .zip(&states)
+        .filter(|(_, state)| **state == LoadState::Failed)
+        .map(|(handle, _)| {
+            asset_server
+                .get_handle_path(handle)
+                .map(|path| path.path().display().to_string())
+                .unwrap_or_else(|| format!("{:?}", handle.id()))
+        })
+        .collect();
+
+    if failed.is_empty() {
+        if loaded == handles.len() {
+            *next_state = NextState(Some(reload.0.take().unwrap_or(GameState::Start)));
+        }
+        return;
+    }
+
+    for path in &failed {
+        error!("Could not load asset {}", path);
+    }
+
+    for (_, mut color) in &mut bar_query {
+        *color = LOADING_FAILED_COLOR.into();
+    }
+
+    let mut message = format!("{}\n\n{}", locale.get("loading_failed"), failed.join("\n"));
+
+    if themes.current != 0 {
+        message.push_str("\n\n");
+        message.push_str(locale.get("loading_failed_theme"));
+    }
+
+    let font = if asset_server.get_load_state(&assets.font.mono) == LoadState::Failed {
+        fallback_font.0.clone()
+    } else {
+        assets.font.mono.clone()
+    };
+
+    for screen in &screen_query {
+        commands.entity(screen).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    message.clone(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                LoadingErrorText,
+            ));
+        });
+    }
 }
@@ -0,0 +1,183 @@
+use crate::*;
+
+const ASSISTS_SAVE: &str = "assists";
+const GAME_SPEEDS: [f32; 3] = [1., 0.75, 0.5];
+const PADDLE_WIDTHS: [f32; 3] = [1., 1.25, 1.5];
+const SAFETY_FLOOR_HEIGHT: f32 = 4.;
+const SAFETY_FLOOR_COLOR: Color = Color::rgba(1., 1., 1., 0.6);
+
+pub struct AssistPlugin;
+
+/// Accessibility options, games played with any of them are flagged in the high scores
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct Assists {
+    /// Multiplier of the game clock, slows down the ball and all timers
+    pub game_speed: f32,
+    /// Multiplier of the paddle width
+    pub paddle_width: f32,
+    /// Bounce the ball off the floor once per level instead of losing it
+    pub safety_floor: bool,
+}
+
+impl Default for Assists {
+    fn default() -> Self {
+        Assists {
+            game_speed: 1.,
+            paddle_width: 1.,
+            safety_floor: false,
+        }
+    }
+}
+
+/// Whether the safety floor of the current level is still there
+#[derive(Resource, Default)]
+pub struct SafetyFloor(pub bool);
+
+#[derive(Component)]
+pub struct SafetyFloorLine;
+
+fn next_step(steps: &[f32], value: f32) -> f32 {
+    let index = steps.iter().position(|step| *step == value).unwrap_or(0);
+
+    steps[(index + 1) % steps.len()]
+}
+
+impl Assists {
+    pub fn is_active(&self) -> bool {
+        *self != Assists::default()
+    }
+
+    pub fn cycle_game_speed(&mut self) {
+        self.game_speed = next_step(&GAME_SPEEDS, self.game_speed);
+    }
+
+    pub fn cycle_paddle_width(&mut self) {
+        self.paddle_width = next_step(&PADDLE_WIDTHS, self.paddle_width);
+    }
+
+    pub fn parse(source: &str) -> Self {
+        let mut assists = Assists::default();
+
+        for line in source.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim() {
+                "game_speed" => {
+                    assists.game_speed = value
+                        .parse()
+                        .ok()
+                        .filter(|speed| GAME_SPEEDS.contains(speed))
+                        .unwrap_or(1.)
+                }
+                "paddle_width" => {
+                    assists.paddle_width = value
+                        .parse()
+                        .ok()
+                        .filter(|width| PADDLE_WIDTHS.contains(width))
+                        .unwrap_or(1.)
+                }
+                "safety_floor" => assists.safety_floor = value == "true",
+                _ => {}
+            }
+        }
+
+        assists
+    }
+
+    pub fn serialize(&self) -> String {
+        format!(
+            "game_speed: {}\npaddle_width: {}\nsafety_floor: {}\n",
+            self.game_speed, self.paddle_width, self.safety_floor
+        )
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = write_save(ASSISTS_SAVE, &self.serialize()) {
+            warn!("Could not save assists: {}", error);
+        }
+    }
+}
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            read_save(ASSISTS_SAVE)
+                .map(|source| Assists::parse(&source))
+                .unwrap_or_default(),
+        )
+        .insert_resource(SafetyFloor::default())
+        .add_system(
+            spawn_safety_floor
+                .after(load_level)
+                .in_schedule(OnEnter(GameState::Playing)),
+        )
+        .add_system(
+            despawn_used_safety_floor
+                .after(ball_loss)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(despawn::<SafetyFloorLine>.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+fn spawn_safety_floor(
+    mut commands: Commands,
+    assists: Res<Assists>,
+    mut safety_floor: ResMut<SafetyFloor>,
+) {
+    safety_floor.0 = assists.safety_floor;
+
+    if safety_floor.0 {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: SAFETY_FLOOR_COLOR,
+                    custom_size: Some(Vec2::new(WIN_WIDTH, SAFETY_FLOOR_HEIGHT)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., (SAFETY_FLOOR_HEIGHT - WIN_HEIGHT) / 2., 10.),
+                ..default()
+            },
+            SafetyFloorLine,
+        ));
+    }
+}
+
+fn despawn_used_safety_floor(
+    mut commands: Commands,
+    safety_floor: Res<SafetyFloor>,
+    query: Query<Entity, With<SafetyFloorLine>>,
+) {
+    if !safety_floor.0 {
+        for entity in &query {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assists_round_trip_and_cycle() {
+        let mut assists = Assists::default();
+        assert!(!assists.is_active());
+
+        assists.cycle_game_speed();
+        assists.cycle_paddle_width();
+        assists.cycle_paddle_width();
+        assists.safety_floor = true;
+
+        assert!(assists.is_active());
+        assert_eq!(assists.game_speed, 0.75);
+        assert_eq!(assists.paddle_width, 1.5);
+        assert_eq!(Assists::parse(&assists.serialize()), assists);
+
+        assists.cycle_paddle_width();
+        assert_eq!(assists.paddle_width, 1.);
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+
+use rand::seq::SliceRandom;
+
+use crate::*;
+
+const MAX_VOICES: usize = 8;
+/// Rough length of the sound effects, a voice counts as busy for this long
+const VOICE_DURATION: f32 = 0.4;
+/// Distance between the ears, which puts them on the walls at pan -1 and 1
+const EAR_GAP: f32 = 2.;
+/// How far in front of the ears sounds play, keeps them from going fully to one side
+const EMITTER_DEPTH: f32 = 0.6;
+const COMBO_PITCH_STEP: f32 = 0.03;
+const MAX_COMBO_PITCH: f32 = 1.5;
+
+pub struct GameAudioPlugin;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SoundEffect {
+    PaddleHit,
+    WallHit,
+    BrickBreak(BrickType),
+    Serve,
+    BallLost,
+    LifeLost,
+    FireballSpawn,
+    FireballExpire,
+    LevelComplete,
+    GameOver,
+}
+
+impl SoundEffect {
+    fn pool<'a>(&self, audio: &'a AudioHandles) -> &'a [Handle<AudioSource>] {
+        match self {
+            SoundEffect::PaddleHit => &audio.paddle_hit,
+            SoundEffect::WallHit => &audio.wall_hit,
+            SoundEffect::BrickBreak(BrickType::Regular) => &audio.brick_break,
+            SoundEffect::BrickBreak(BrickType::Fireball) => &audio.fireball_break,
+            SoundEffect::BrickBreak(BrickType::Bomb) => &audio.bomb_break,
+            SoundEffect::Serve => &audio.serve,
+            SoundEffect::BallLost => &audio.ball_lost,
+            SoundEffect::LifeLost => &audio.life_lost,
+            SoundEffect::FireballSpawn => &audio.fireball_spawn,
+            SoundEffect::FireballExpire => &audio.fireball_expire,
+            SoundEffect::LevelComplete => &audio.level_complete,
+            SoundEffect::GameOver => &audio.game_over,
+        }
+    }
+
+    /// Volume, base pitch and random pitch spread
+    fn settings(&self) -> (f32, f32, f32) {
+        match self {
+            SoundEffect::PaddleHit => (1., 1., 0.05),
+            SoundEffect::WallHit => (0.3, 1., 0.1),
+            SoundEffect::BrickBreak(BrickType::Regular) => (1., 1., 0.2),
+            SoundEffect::BrickBreak(BrickType::Fireball) => (1., 1.3, 0.1),
+            SoundEffect::BrickBreak(BrickType::Bomb) => (1., 0.6, 0.1),
+            SoundEffect::Serve => (0.6, 1.4, 0.05),
+            SoundEffect::BallLost => (0.8, 0.5, 0.05),
+            SoundEffect::LifeLost => (1., 0.4, 0.),
+            SoundEffect::FireballSpawn => (0.8, 1.6, 0.1),
+            SoundEffect::FireballExpire => (0.5, 0.7, 0.1),
+            SoundEffect::LevelComplete => (0.8, 1., 0.),
+            SoundEffect::GameOver => (1., 0.5, 0.),
+        }
+    }
+
+    /// Pitches played together, stingers sound as a chord
+    fn chord(&self) -> &'static [f32] {
+        match self {
+            SoundEffect::LifeLost => &[1., 0.8],
+            SoundEffect::LevelComplete => &[1., 1.26, 1.5, 2.],
+            SoundEffect::GameOver => &[1., 0.84, 0.67],
+            _ => &[1.],
+        }
+    }
+
+    /// Stingers always play, even when a cascade has taken every voice
+    fn is_stinger(&self) -> bool {
+        matches!(
+            self,
+            SoundEffect::LifeLost | SoundEffect::LevelComplete | SoundEffect::GameOver
+        )
+    }
+}
+
+/// Stereo position from -1 (left wall) to 1 (right wall)
+pub fn pan(x: f32) -> f32 {
+    (x / (WIN_WIDTH / 2.)).clamp(-1., 1.)
+}
+
+/// Brick sounds rise in pitch as the chain grows
+pub fn combo_pitch(chain: u32) -> f32 {
+    (1. + chain.saturating_sub(1) as f32 * COMBO_PITCH_STEP).min(MAX_COMBO_PITCH)
+}
+
+/// Start times of the sounds still playing, so cascades cannot pile up and clip
+#[derive(Resource, Default)]
+pub struct Voices(VecDeque<f32>);
+
+impl Voices {
+    /// Takes `count` free voices, or none if there are not enough
+    pub fn claim(&mut self, now: f32, count: usize) -> bool {
+        self.release_finished(now);
+
+        if self.0.len() + count > MAX_VOICES {
+            return false;
+        }
+
+        for _ in 0..count {
+            self.0.push_back(now);
+        }
+        true
+    }
+
+    /// Takes `count` voices even when over the cap, later sounds wait until they finish
+    pub fn force(&mut self, now: f32, count: usize) {
+        self.release_finished(now);
+        for _ in 0..count {
+            self.0.push_back(now);
+        }
+    }
+
+    fn release_finished(&mut self, now: f32) {
+        while self
+            .0
+            .front()
+            .is_some_and(|start| now - start > VOICE_DURATION)
+        {
+            self.0.pop_front();
+        }
+    }
+}
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Voices::default())
+            .add_system(play_sounds.after(update_score));
+    }
+}
+
+fn play_sound(
+    audio: &Audio,
+    assets: &GameAssets,
+    voices: &mut Voices,
+    time: &Time,
+    effect: SoundEffect,
+    x: f32,
+    pitch: f32,
+) {
+    let Some(source) = effect.pool(&assets.audio).choose(&mut rand::thread_rng()) else {
+        return;
+    };
+
+    // Real time, hit-stop freezes the game clock, every note of a chord is a voice
+    let (now, notes) = (time.raw_elapsed_seconds(), effect.chord().len());
+
+    if effect.is_stinger() {
+        voices.force(now, notes);
+    } else if !voices.claim(now, notes) {
+        return;
+    }
+
+    let (volume, base_pitch, spread) = effect.settings();
+    let variation = 1. + (rand::random::<f32>() * 2. - 1.) * spread;
+
+    for note in effect.chord() {
+        audio.play_spatial_with_settings(
+            source.clone(),
+            PlaybackSettings::ONCE
+                .with_volume(volume)
+                .with_speed(base_pitch * pitch * variation * note),
+            Transform::IDENTITY,
+            EAR_GAP,
+            Vec3::new(pan(x), 0., EMITTER_DEPTH),
+        );
+    }
+}
+
+/// Every game sound goes through here, picked by the event that caused it
+#[allow(clippy::too_many_arguments)]
+fn play_sounds(
+    mut collision_events: EventReader<BallCollisionEvent>,
+    mut brick_destruction_events: EventReader<BrickDesctructionEvent>,
+    mut launched_events: EventReader<BallLaunchedEvent>,
+    mut ball_lost_events: EventReader<BallLostEvent>,
+    mut life_lost_events: EventReader<LifeLostEvent>,
+    mut powerup_collected_events: EventReader<PowerupCollectedEvent>,
+    mut powerup_expired_events: EventReader<PowerupExpiredEvent>,
+    mut level_cleared_events: EventReader<LevelClearedEvent>,
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut voices: ResMut<Voices>,
+    combo: Res<ComboChain>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    time: Res<Time>,
+) {
+    let mut play = |effect, x, pitch| {
+        play_sound(&audio, &assets, &mut voices, &time, effect, x, pitch);
+    };
+
+    let positioned = launched_events
+        .iter()
+        .map(|event| (SoundEffect::Serve, event.position))
+        .chain(
+            ball_lost_events
+                .iter()
+                .map(|event| (SoundEffect::BallLost, event.position)),
+        )
+        .chain(
+            powerup_collected_events
+                .iter()
+                .map(|event| match event.powerup {
+                    Powerup::Fireball => (SoundEffect::FireballSpawn, event.position),
+                }),
+        )
+        .chain(
+            powerup_expired_events
+                .iter()
+                .map(|event| match event.powerup {
+                    Powerup::Fireball => (SoundEffect::FireballExpire, event.position),
+                }),
+        );
+
+    for (effect, position) in positioned {
+        play(effect, position.x, 1.);
+    }
+
+    for _ in life_lost_events.iter() {
+        play(SoundEffect::LifeLost, 0., 1.);
+    }
+
+    for _ in level_cleared_events.iter() {
+        play(SoundEffect::LevelComplete, 0., 1.);
+    }
+
+    for _ in game_over_events.iter() {
+        play(SoundEffect::GameOver, 0., 1.);
+    }
+
+    for event in brick_destruction_events.iter() {
+        play(
+            SoundEffect::BrickBreak(event.brick_type),
+            event.position.x,
+            combo_pitch(combo.count),
+        );
+    }
+
+    for event in collision_events.iter() {
+        let effect = match event.collision_type {
+            BallCollisionType::Paddle => SoundEffect::PaddleHit,
+            BallCollisionType::Wall(_) => SoundEffect::WallHit,
+        };
+
+        play(effect, event.position.x, 1.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voices_are_capped_until_they_finish() {
+        let mut voices = Voices::default();
+
+        assert!((0..MAX_VOICES - 2).all(|_| voices.claim(1., 1)));
+        assert!(!voices.claim(1.1, 3));
+        assert!(voices.claim(1.1, 2));
+        assert!(!voices.claim(1.1, 1));
+        assert!(voices.claim(1. + VOICE_DURATION * 1.5, 1));
+
+        voices.force(2., MAX_VOICES + 1);
+        assert!(!voices.claim(2.1, 1));
+    }
+
+    #[test]
+    fn pitch_and_pan_stay_in_range() {
+        assert_eq!(combo_pitch(0), 1.);
+        assert_eq!(combo_pitch(1), 1.);
+        assert!(combo_pitch(5) > combo_pitch(4));
+        assert_eq!(combo_pitch(1000), MAX_COMBO_PITCH);
+
+        assert_eq!(pan(0.), 0.);
+        assert_eq!(pan(WIN_WIDTH), 1.);
+        assert_eq!(pan(-WIN_WIDTH / 4.), -0.5);
+    }
+}
@@ -3,12 +3,22 @@ use bevy::{ecs::system::Command, prelude::*};
 use crate::*;
 
 pub const BALL_SIZE: f32 = 22.;
+const BRICK_VELOCITY_TRANSFER: f32 = 0.5;
 
 pub struct AllBallsLostEvent;
 
-pub struct BallCollisionEvent(pub BallCollisionType);
+pub struct BallCollisionEvent {
+    pub collision_type: BallCollisionType,
+    pub position: Vec3,
+    pub ball: Entity,
+    pub ball_type: BallType,
+    /// Surface normal at the point of impact
+    pub normal: Vec2,
+    /// Speed of the ball at impact
+    pub speed: f32,
+}
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum BallType {
     Regular,
     FireBall,
@@ -29,7 +39,7 @@ pub struct FireBall {
 
 #[derive(PartialEq)]
 pub enum BallCollisionType {
-    Wall,
+    Wall(WallSide),
     Paddle,
 }
 
@@ -87,11 +97,23 @@ fn ball_movement(
 pub fn ball_loss(
     mut commands: Commands,
     mut ball_loss_event: EventWriter<AllBallsLostEvent>,
-    mut ball_query: Query<(Entity, &mut Transform), With<Ball>>,
+    mut lost_events: EventWriter<BallLostEvent>,
+    mut safety_floor: ResMut<SafetyFloor>,
+    mut ball_query: Query<(Entity, &mut Ball, &mut Transform)>,
 ) {
-    for (entity, transform) in ball_query.iter_mut() {
+    for (entity, mut ball, transform) in ball_query.iter_mut() {
         if transform.translation.y < -WIN_HEIGHT / 2. {
-            commands.entity(entity).despawn();
+            // The safety floor bounces a regular ball back once, fireballs burn out anyway
+            if safety_floor.0 && ball.ball_type == BallType::Regular {
+                safety_floor.0 = false;
+                ball.direction.y = ball.direction.y.abs();
+            } else {
+                commands.entity(entity).despawn();
+                lost_events.send(BallLostEvent {
+                    position: transform.translation,
+                    ball_type: ball.ball_type,
+                });
+            }
         }
     }
 
@@ -100,29 +122,64 @@ pub fn ball_loss(
     }
 }
 
+/// Side of the other collider the ball hit, as the normal pointing back at the ball
+pub fn collision_normal(collision: &Collision, direction: Vec2) -> Vec2 {
+    match collision {
+        Collision::Left => Vec2::NEG_X,
+        Collision::Right => Vec2::X,
+        Collision::Top => Vec2::Y,
+        Collision::Bottom => Vec2::NEG_Y,
+        Collision::Inside => -direction.normalize_or_zero(),
+    }
+}
+
 fn check_wall_collisions(
-    mut ball_query: Query<(&mut Ball, &mut Transform), Without<AttachedToPaddle>>,
+    mut ball_query: Query<(Entity, &mut Ball, &Collider, &Transform), Without<AttachedToPaddle>>,
+    wall_query: Query<(&Wall, &Collider, &Transform), Without<Ball>>,
     mut collision_events: EventWriter<BallCollisionEvent>,
 ) {
-    for (mut ball, ball_transform) in ball_query.iter_mut() {
-        if ball_transform.translation.x + BALL_SIZE / 2. > WIN_WIDTH / 2. {
-            ball.direction.x = -ball.direction.x.abs();
-            collision_events.send(BallCollisionEvent(BallCollisionType::Wall));
-        } else if ball_transform.translation.x - BALL_SIZE / 2. < -WIN_WIDTH / 2. {
-            ball.direction.x = ball.direction.x.abs();
-            collision_events.send(BallCollisionEvent(BallCollisionType::Wall));
-        } else if ball_transform.translation.y + BALL_SIZE / 2. > WIN_HEIGHT / 2. {
-            ball.direction.y = -ball.direction.y.abs();
-            collision_events.send(BallCollisionEvent(BallCollisionType::Wall));
+    for (entity, mut ball, ball_collider, ball_transform) in ball_query.iter_mut() {
+        for (wall, wall_collider, wall_transform) in wall_query.iter() {
+            let collision = collide(
+                ball_transform.translation,
+                ball_collider.size,
+                wall_transform.translation,
+                wall_collider.size,
+            );
+
+            if collision.is_none() {
+                continue;
+            }
+
+            // Always send the ball back into the arena, whichever face it overlaps
+            let normal = wall.side.normal();
+            match wall.side {
+                WallSide::Left => ball.direction.x = ball.direction.x.abs(),
+                WallSide::Right => ball.direction.x = -ball.direction.x.abs(),
+                WallSide::Ceiling => ball.direction.y = -ball.direction.y.abs(),
+            }
+
+            collision_events.send(BallCollisionEvent {
+                collision_type: BallCollisionType::Wall(wall.side),
+                position: ball_transform.translation,
+                ball: entity,
+                ball_type: ball.ball_type,
+                normal,
+                speed: ball.speed,
+            });
         }
     }
 }
 
-fn check_collisions(
+#[allow(clippy::type_complexity)]
+pub fn check_collisions(
     mut commands: Commands,
     mut brick_events: EventWriter<BrickDesctructionEvent>,
     mut collision_events: EventWriter<BallCollisionEvent>,
-    mut ball_query: Query<(&mut Ball, &Collider, &mut Transform), Without<AttachedToPaddle>>,
+    mut ball_query: Query<
+        (Entity, &mut Ball, &Collider, &mut Transform),
+        Without<AttachedToPaddle>,
+    >,
     mut collider_query: Query<
         (
             Entity,
@@ -130,14 +187,17 @@ fn check_collisions(
             &Transform,
             Option<&Brick>,
             Option<&Paddle>,
+            Option<&MovingBrick>,
         ),
-        Without<Ball>,
+        (Without<Ball>, Without<Wall>),
     >,
 ) {
-    let mut bricks_to_despawn = Vec::new();
+    let mut destroyed_bricks: Vec<(Entity, BrickDesctructionEvent)> = Vec::new();
 
-    for (mut ball, ball_collider, ball_transform) in ball_query.iter_mut() {
-        for (entity, other_collider, other_transform, brick, paddle) in collider_query.iter_mut() {
+    for (ball_entity, mut ball, ball_collider, ball_transform) in ball_query.iter_mut() {
+        for (entity, other_collider, other_transform, brick, paddle, moving_brick) in
+            collider_query.iter_mut()
+        {
             let collision = collide(
                 ball_transform.translation,
                 ball_collider.size,
@@ -145,19 +205,32 @@ fn check_collisions(
                 other_collider.size,
             );
 
-            if collision.is_some() {
+            if let Some(collision) = collision {
+                let normal = collision_normal(&collision, ball.direction);
+
                 if let Some(brick) = brick {
-                    if !bricks_to_despawn.contains(&entity) {
-                        bricks_to_despawn.push(entity);
+                    if !destroyed_bricks.iter().any(|(e, _)| *e == entity) {
+                        destroyed_bricks.push((
+                            entity,
+                            BrickDesctructionEvent {
+                                brick: entity,
+                                position: other_transform.translation,
+                                brick_type: brick.brick_type,
+                                color: brick.color,
+                                cell: brick.cell,
+                                chain_depth: 0,
+                                ball: ball_entity,
+                                ball_type: ball.ball_type,
+                                normal,
+                                speed: ball.speed,
+                            },
+                        ));
                     }
-
-                    brick_events.send(BrickDesctructionEvent {
-                        position: other_transform.translation,
-                        brick_type: brick.brick_type.clone(),
-                    });
                 } else if let Some(paddle) = paddle {
-                    // Reflection based on paddle hit point
-                    let delta = ball_transform.translation.x - other_transform.translation.x;
+                    // Reflection based on paddle hit point, relative to the paddle width
+                    let delta = (ball_transform.translation.x - other_transform.translation.x)
+                        * PADDLE_WIDTH
+                        / other_collider.size.x;
                     ball.direction.x += delta * 0.008;
 
                     // Curve balls
@@ -167,26 +240,82 @@ fn check_collisions(
 
                     // Bounce up
                     ball.direction.y = ball.direction.y.abs();
-                    collision_events.send(BallCollisionEvent(BallCollisionType::Paddle));
+                    collision_events.send(BallCollisionEvent {
+                        collision_type: BallCollisionType::Paddle,
+                        position: ball_transform.translation,
+                        ball: ball_entity,
+                        ball_type: ball.ball_type,
+                        normal,
+                        speed: ball.speed,
+                    });
                 }
 
                 if ball.ball_type != BallType::FireBall {
                     match collision {
-                        Some(Collision::Left) => ball.direction.x = -ball.direction.x.abs(),
-                        Some(Collision::Right) => ball.direction.x = ball.direction.x.abs(),
-                        Some(Collision::Top) => ball.direction.y = ball.direction.y.abs(),
-                        Some(Collision::Bottom) => ball.direction.y = -ball.direction.y.abs(),
-                        _ => {}
+                        Collision::Left => ball.direction.x = -ball.direction.x.abs(),
+                        Collision::Right => ball.direction.x = ball.direction.x.abs(),
+                        Collision::Top => ball.direction.y = ball.direction.y.abs(),
+                        Collision::Bottom => ball.direction.y = -ball.direction.y.abs(),
+                        Collision::Inside => {}
                     }
                 }
 
+                // Moving bricks push the ball along
+                if let Some(moving_brick) = moving_brick {
+                    let velocity = ball.direction.normalize() * ball.speed
+                        + moving_brick.velocity * BRICK_VELOCITY_TRANSFER;
+                    ball.direction = velocity.normalize();
+                }
+
                 break;
             }
         }
     }
 
-    for entity in bricks_to_despawn.iter() {
-        commands.entity(*entity).despawn_recursive();
+    // Bombs take out everything in range, which may set off more bombs
+    let mut i = 0;
+
+    while i < destroyed_bricks.len() {
+        let bomb = &destroyed_bricks[i].1;
+        let (bomb_position, chain_depth) = (bomb.position, bomb.chain_depth);
+        let (ball, ball_type, speed) = (bomb.ball, bomb.ball_type, bomb.speed);
+
+        if bomb.brick_type == BrickType::Bomb {
+            for (entity, _, transform, brick, ..) in collider_query.iter() {
+                let Some(brick) = brick else {
+                    continue;
+                };
+
+                if transform.translation.distance(bomb_position) < BOMB_RADIUS
+                    && !destroyed_bricks.iter().any(|(e, _)| *e == entity)
+                {
+                    destroyed_bricks.push((
+                        entity,
+                        BrickDesctructionEvent {
+                            brick: entity,
+                            position: transform.translation,
+                            brick_type: brick.brick_type,
+                            color: brick.color,
+                            cell: brick.cell,
+                            chain_depth: chain_depth + 1,
+                            ball,
+                            ball_type,
+                            normal: (transform.translation - bomb_position)
+                                .truncate()
+                                .normalize_or_zero(),
+                            speed,
+                        },
+                    ));
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    for (entity, event) in destroyed_bricks {
+        brick_events.send(event);
+        commands.entity(entity).despawn_recursive();
     }
 }
 
@@ -198,22 +327,18 @@ pub struct SpawnBallCommand;
 
 impl Command for SpawnBallCommand {
     fn write(self, world: &mut World) {
-        let assets = world.get_resource::<GameAssets>();
-        let progress = world.get_resource::<PlayerProgress>();
+        let sheet = world.get_resource::<SpriteSheet>();
+        let level = world.get_resource::<CurrentLevel>();
 
-        if let Some(assets) = assets {
+        if let Some(sheet) = sheet {
             world.spawn((
                 Ball {
                     direction: Vec2::new(rand::random::<f32>() * 2. - 1., 1.),
-                    speed: 300. + progress.unwrap().level as f32 * 50.,
+                    speed: level.unwrap().0.ball_speed,
                     curve: 0.,
                     ball_type: BallType::Regular,
                 },
-                SpriteBundle {
-                    texture: assets.image.ball.clone(),
-                    transform: Transform::from_xyz(0., 0., 10.),
-                    ..default()
-                },
+                sheet.bundle(&sheet.ball, Transform::from_xyz(0., 0., 10.)),
                 Collider {
                     size: Vec2::splat(BALL_SIZE),
                 },
@@ -226,13 +351,18 @@ impl Command for SpawnBallCommand {
 fn expire_fireballs(
     time: Res<Time>,
     mut commands: Commands,
-    mut query: Query<(Entity, &mut FireBall)>,
+    mut expired_events: EventWriter<PowerupExpiredEvent>,
+    mut query: Query<(Entity, &mut FireBall, &Transform)>,
 ) {
-    for (entity, mut fireball) in query.iter_mut() {
+    for (entity, mut fireball, transform) in query.iter_mut() {
         fireball.age += time.delta_seconds();
 
         if fireball.age > MAX_FIREBALL_AGE {
             commands.entity(entity).despawn();
+            expired_events.send(PowerupExpiredEvent {
+                powerup: Powerup::Fireball,
+                position: transform.translation,
+            });
         }
     }
 }
@@ -0,0 +1,171 @@
+use crate::*;
+
+const SHAKE_DURATION: f32 = 0.25;
+const SHAKE_BASE_INTENSITY: f32 = 2.;
+const SHAKE_MAX_INTENSITY: f32 = 12.;
+const HIT_STOP_DURATION: f32 = 0.08;
+const FLASH_DURATION: f32 = 0.4;
+const FLASH_Z_VALUE: f32 = 90.;
+
+pub struct CameraEffectsPlugin;
+
+#[derive(Clone, Copy)]
+pub enum CameraEffectKind {
+    Shake { intensity: f32 },
+    HitStop,
+    Flash { color: Color },
+}
+
+pub struct CameraEffect {
+    pub kind: CameraEffectKind,
+    pub duration: f32,
+    pub age: f32,
+}
+
+/// Active camera effects, applied on top of each other every frame
+#[derive(Resource)]
+pub struct CameraEffects {
+    /// Accessibility toggle, no effects are added while disabled
+    pub enabled: bool,
+    pub stack: Vec<CameraEffect>,
+}
+
+impl CameraEffects {
+    pub fn push(&mut self, kind: CameraEffectKind, duration: f32) {
+        if self.enabled {
+            self.stack.push(CameraEffect {
+                kind,
+                duration,
+                age: 0.,
+            });
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct FlashOverlay;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraEffects {
+            enabled: true,
+            stack: vec![],
+        })
+        .add_startup_system(spawn_flash_overlay)
+        .add_system(apply_camera_effects)
+        .add_system(
+            trigger_camera_effects
+                .after(update_score)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+    }
+}
+
+fn spawn_flash_overlay(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::NONE,
+                custom_size: Some(Vec2::new(WIN_WIDTH, WIN_HEIGHT)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 0., FLASH_Z_VALUE),
+            ..default()
+        },
+        FlashOverlay,
+    ));
+}
+
+fn trigger_camera_effects(
+    mut effects: ResMut<CameraEffects>,
+    mut destruction_events: EventReader<BrickDesctructionEvent>,
+    mut ball_loss_events: EventReader<AllBallsLostEvent>,
+    combo: Res<ComboChain>,
+) {
+    for event in destruction_events.iter() {
+        effects.push(
+            CameraEffectKind::Shake {
+                intensity: shake_intensity(combo.count),
+            },
+            SHAKE_DURATION,
+        );
+
+        if event.brick_type == BrickType::Fireball {
+            effects.push(CameraEffectKind::HitStop, HIT_STOP_DURATION);
+        }
+    }
+
+    if ball_loss_events.iter().count() > 0 {
+        effects.push(
+            CameraEffectKind::Flash {
+                color: Color::rgba(1., 0.1, 0.1, 0.5),
+            },
+            FLASH_DURATION,
+        );
+    }
+}
+
+/// Every brick shakes the screen, harder the longer the combo chain
+fn shake_intensity(combo: u32) -> f32 {
+    (SHAKE_BASE_INTENSITY * (1. + combo.saturating_sub(1) as f32 * 0.5)).min(SHAKE_MAX_INTENSITY)
+}
+
+fn apply_camera_effects(
+    mut effects: ResMut<CameraEffects>,
+    mut time: ResMut<Time>,
+    assists: Res<Assists>,
+    state: Res<State<GameState>>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    mut overlay_query: Query<&mut Sprite, With<FlashOverlay>>,
+) {
+    if !effects.enabled {
+        effects.stack.clear();
+    }
+
+    // Effects run on real time so hit-stop does not freeze itself
+    let delta = time.raw_delta_seconds();
+    let mut shake = Vec2::ZERO;
+    let mut hit_stop = false;
+    let mut flash = Color::NONE;
+
+    for effect in effects.stack.iter_mut() {
+        effect.age += delta;
+
+        let remaining = (1. - effect.age / effect.duration).max(0.);
+
+        match effect.kind {
+            CameraEffectKind::Shake { intensity } => {
+                shake += Vec2::new(
+                    rand::random::<f32>() * 2. - 1.,
+                    rand::random::<f32>() * 2. - 1.,
+                ) * intensity
+                    * remaining;
+            }
+            CameraEffectKind::HitStop => hit_stop = true,
+            CameraEffectKind::Flash { color } => {
+                flash = color.with_a(color.a() * remaining);
+            }
+        }
+    }
+
+    effects.stack.retain(|effect| effect.age < effect.duration);
+
+    // The game speed assist only slows gameplay, menus and screens between levels run at full speed
+    let speed = if hit_stop {
+        0.
+    } else if state.0 == GameState::Playing {
+        assists.game_speed
+    } else {
+        1.
+    };
+    time.set_relative_speed(speed);
+
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = shake.x;
+        transform.translation.y = shake.y;
+    }
+
+    for mut sprite in overlay_query.iter_mut() {
+        sprite.color = flash;
+    }
+}
@@ -0,0 +1,445 @@
+use bevy::sprite::Anchor;
+
+use crate::*;
+
+const EDITOR_BALL_SPEED_STEP: f32 = 25.;
+const EDITOR_MAX_LIVES: u8 = 9;
+const EDITOR_DESCEND_SPEED: f32 = 3.;
+const EDITOR_REGROW_TIME: f32 = 10.;
+const EDITOR_Z_VALUE: f32 = 100.;
+
+pub struct EditorPlugin;
+
+/// Set while the level being played comes from the editor
+#[derive(Resource, Default)]
+pub struct EditorPlaytest(pub bool);
+
+#[derive(Resource)]
+pub struct EditorLevel(pub Level);
+
+#[derive(Resource)]
+pub struct EditorBrush(pub LevelBrick);
+
+#[derive(Resource, Default)]
+pub struct EditorStatus(pub String);
+
+/// Level number the generator targets when filling the editor grid
+#[derive(Resource)]
+pub struct EditorGeneratorLevel(pub u16);
+
+#[derive(Component)]
+pub struct EditorBrick;
+
+#[derive(Component)]
+pub struct EditorGridCell;
+
+#[derive(Component)]
+pub struct EditorCursor;
+
+#[derive(Component)]
+pub struct EditorParameterText;
+
+#[derive(Component)]
+pub struct EditorHelpText;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EditorPlaytest(false))
+            .insert_resource(EditorLevel(
+                Level::load(CUSTOM_LEVEL_PATH)
+                    .unwrap_or_else(|_| generate_level(rand::random(), 1)),
+            ))
+            .insert_resource(EditorBrush(LevelBrick {
+                color: BrickColor::Red,
+                brick_type: BrickType::Regular,
+                path: None,
+            }))
+            .insert_resource(EditorStatus::default())
+            .insert_resource(EditorGeneratorLevel(1));
+
+        app.add_system(stop_playtest.in_set(OnUpdate(GameState::Playing)));
+
+        // Editor state
+        app.add_systems(
+            (
+                show_cursor,
+                reset_player_progress,
+                spawn_editor_grid,
+                spawn_editor_bricks,
+                spawn_editor_cursor,
+                spawn_editor_text,
+            )
+                .in_schedule(OnEnter(GameState::Editor)),
+        )
+        .add_systems(
+            (
+                select_brush,
+                paint_bricks,
+                edit_level_parameters,
+                generate_editor_level,
+                save_level,
+                start_playtest,
+                close_editor,
+                spawn_editor_bricks.run_if(resource_changed::<EditorLevel>()),
+                update_editor_cursor,
+                update_editor_text,
+            )
+                .in_set(OnUpdate(GameState::Editor)),
+        )
+        .add_systems(
+            (
+                despawn::<EditorBrick>,
+                despawn::<EditorGridCell>,
+                despawn::<EditorCursor>,
+                despawn::<EditorParameterText>,
+                despawn::<EditorHelpText>,
+            )
+                .in_schedule(OnExit(GameState::Editor)),
+        );
+    }
+}
+
+fn close_editor(
+    key: Res<Input<KeyCode>>,
+    mut playtest: ResMut<EditorPlaytest>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if key.just_pressed(KeyCode::Escape) {
+        playtest.0 = false;
+        *next_state = NextState(Some(GameState::Start));
+    }
+}
+
+fn start_playtest(
+    key: Res<Input<KeyCode>>,
+    mut playtest: ResMut<EditorPlaytest>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if key.just_pressed(KeyCode::Return) {
+        playtest.0 = true;
+        *next_state = NextState(Some(GameState::Playing));
+    }
+}
+
+fn stop_playtest(
+    key: Res<Input<KeyCode>>,
+    playtest: Res<EditorPlaytest>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if playtest.0 && key.just_pressed(KeyCode::Return) {
+        *next_state = NextState(Some(GameState::Editor));
+    }
+}
+
+fn cursor_world_position(windows: &Query<&Window>) -> Option<Vec2> {
+    let window = windows.get_single().ok()?;
+    let position = window.cursor_position()?;
+
+    Some(position - Vec2::new(window.width(), window.height()) / 2.)
+}
+
+fn select_brush(key: Res<Input<KeyCode>>, mut brush: ResMut<EditorBrush>) {
+    let color_keys = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+    ];
+
+    for (color_key, color) in color_keys.iter().zip(BrickColor::ALL) {
+        if key.just_pressed(*color_key) {
+            brush.0.color = color;
+        }
+    }
+
+    if key.just_pressed(KeyCode::F) {
+        brush.0.brick_type = match brush.0.brick_type {
+            BrickType::Regular => BrickType::Fireball,
+            BrickType::Fireball => BrickType::Bomb,
+            BrickType::Bomb => BrickType::Regular,
+        };
+    }
+
+    if key.just_pressed(KeyCode::M) {
+        brush.0.path = match brush.0.path {
+            None => Some(BrickPath::Sweep {
+                amplitude: BRICK_WIDTH / 2.,
+                speed: 1.5,
+            }),
+            Some(BrickPath::Sweep { .. }) => Some(BrickPath::Orbit {
+                radius: BRICK_HEIGHT / 2.,
+                speed: 2.,
+            }),
+            Some(BrickPath::Orbit { .. }) => None,
+        };
+    }
+}
+
+fn paint_bricks(
+    windows: Query<&Window>,
+    btn: Res<Input<MouseButton>>,
+    brush: Res<EditorBrush>,
+    mut level: ResMut<EditorLevel>,
+) {
+    let Some((x, y)) = cursor_world_position(&windows).and_then(brick_cell) else {
+        return;
+    };
+
+    // Bricks behind the walls of a level with custom bounds could never be hit
+    let cell = if btn.pressed(MouseButton::Left) && level.0.bounds.holds_cell(x, y) {
+        Some(brush.0)
+    } else if btn.pressed(MouseButton::Right) {
+        None
+    } else {
+        return;
+    };
+
+    // Only touch the resource on actual changes to avoid respawning every frame
+    if level.0.bricks[y][x] != cell {
+        level.0.bricks[y][x] = cell;
+    }
+}
+
+fn edit_level_parameters(key: Res<Input<KeyCode>>, mut level: ResMut<EditorLevel>) {
+    if key.just_pressed(KeyCode::Up) {
+        level.0.ball_speed += EDITOR_BALL_SPEED_STEP;
+    }
+
+    if key.just_pressed(KeyCode::Down) {
+        level.0.ball_speed = (level.0.ball_speed - EDITOR_BALL_SPEED_STEP).max(100.);
+    }
+
+    if key.just_pressed(KeyCode::Right) {
+        level.0.lives = (level.0.lives + 1).min(EDITOR_MAX_LIVES);
+    }
+
+    if key.just_pressed(KeyCode::Left) {
+        level.0.lives = level.0.lives.saturating_sub(1);
+    }
+
+    if key.just_pressed(KeyCode::D) {
+        level.0.rules.descend_speed = if level.0.rules.descend_speed > 0. {
+            0.
+        } else {
+            EDITOR_DESCEND_SPEED
+        };
+    }
+
+    if key.just_pressed(KeyCode::R) {
+        level.0.rules.regrow_time = match level.0.rules.regrow_time {
+            Some(_) => None,
+            None => Some(EDITOR_REGROW_TIME),
+        };
+    }
+
+    if key.just_pressed(KeyCode::K) {
+        level.0.rules.shrink_paddle = !level.0.rules.shrink_paddle;
+    }
+
+    if key.just_pressed(KeyCode::C) {
+        level.0.bricks = Level::default().bricks;
+    }
+}
+
+fn generate_editor_level(
+    key: Res<Input<KeyCode>>,
+    mut generator_level: ResMut<EditorGeneratorLevel>,
+    mut level: ResMut<EditorLevel>,
+) {
+    if key.just_pressed(KeyCode::PageUp) {
+        generator_level.0 = generator_level.0.saturating_add(1);
+    }
+
+    if key.just_pressed(KeyCode::PageDown) {
+        generator_level.0 = generator_level.0.saturating_sub(1).max(1);
+    }
+
+    if key.just_pressed(KeyCode::G) {
+        level.0 = generate_level(rand::random(), generator_level.0);
+    }
+}
+
+fn save_level(
+    key: Res<Input<KeyCode>>,
+    level: Res<EditorLevel>,
+    mut status: ResMut<EditorStatus>,
+    locale: Res<Locale>,
+) {
+    if key.just_pressed(KeyCode::S) {
+        status.0 = match level.0.save(CUSTOM_LEVEL_PATH) {
+            Ok(()) => locale.format("editor_saved", &[&CUSTOM_LEVEL_PATH]),
+            Err(error) => {
+                warn!("Could not save level: {}", error);
+                locale.format("editor_save_failed", &[&error])
+            }
+        };
+    }
+}
+
+fn spawn_editor_grid(mut commands: Commands) {
+    for y in 0..LEVEL_ROWS {
+        for x in 0..BRICK_COLUMNS {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(1., 1., 1., 0.08),
+                        custom_size: Some(Vec2::new(BRICK_WIDTH - 2., BRICK_HEIGHT - 2.)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(brick_position(x, y) - Vec3::Z * 5.),
+                    ..default()
+                },
+                EditorGridCell,
+            ));
+        }
+    }
+}
+
+fn spawn_editor_bricks(
+    mut commands: Commands,
+    query: Query<Entity, With<EditorBrick>>,
+    sheet: Res<SpriteSheet>,
+    level: Res<EditorLevel>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for (y, row) in level.0.bricks.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if let Some(level_brick) = cell {
+                let entity = spawn_brick(
+                    &mut commands,
+                    &sheet,
+                    level_brick,
+                    (x, y),
+                    brick_position(x, y),
+                );
+                commands.entity(entity).insert(EditorBrick);
+            }
+        }
+    }
+}
+
+fn spawn_editor_cursor(mut commands: Commands, sheet: Res<SpriteSheet>) {
+    let mut bundle = sheet.bundle(&sheet.ball, Transform::default());
+    bundle.visibility = Visibility::Hidden;
+
+    commands.spawn((bundle, EditorCursor));
+}
+
+fn update_editor_cursor(
+    windows: Query<&Window>,
+    mut query: Query<
+        (&mut Transform, &mut TextureAtlasSprite, &mut Visibility),
+        With<EditorCursor>,
+    >,
+    brush: Res<EditorBrush>,
+    sheet: Res<SpriteSheet>,
+    palette: Res<Palette>,
+) {
+    let cell = cursor_world_position(&windows).and_then(brick_cell);
+    let (brick_type, color) = (brush.0.brick_type, brush.0.color);
+
+    for (mut transform, mut sprite, mut visibility) in &mut query {
+        if let Some((x, y)) = cell {
+            transform.translation = brick_position(x, y) + Vec3::Z * 20.;
+            // Same look as the bricks it paints
+            (sprite.index, sprite.color) = match (brick_type, *palette) {
+                (BrickType::Fireball, _) => (sheet.ball_fire.first(), Color::WHITE),
+                (_, Palette::Standard) => (sheet.brick(brick_type, color).first(), Color::WHITE),
+                _ => (
+                    sheet.grey_brick(brick_type).first(),
+                    palette.brick_color(color),
+                ),
+            };
+            sprite.color.set_a(0.6);
+            *visibility = Visibility::Visible;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn spawn_editor_text(mut commands: Commands, assets: Res<GameAssets>, locale: Res<Locale>) {
+    let style = TextStyle {
+        font: assets.font.bold.clone(),
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections([
+                TextSection::new("", style.clone()),
+                TextSection::new(
+                    "",
+                    TextStyle {
+                        color: Color::GOLD,
+                        ..style.clone()
+                    },
+                ),
+            ]),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform::from_xyz(
+                -WIN_WIDTH / 2. + 20.,
+                WIN_HEIGHT / 2. - 20.,
+                EDITOR_Z_VALUE,
+            ),
+            ..default()
+        },
+        EditorParameterText,
+    ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(locale.get("editor_help"), style),
+            text_anchor: Anchor::BottomLeft,
+            transform: Transform::from_xyz(
+                -WIN_WIDTH / 2. + 20.,
+                -WIN_HEIGHT / 2. + 20.,
+                EDITOR_Z_VALUE,
+            ),
+            ..default()
+        },
+        EditorHelpText,
+    ));
+}
+
+fn update_editor_text(
+    mut query: Query<&mut Text, With<EditorParameterText>>,
+    level: Res<EditorLevel>,
+    brush: Res<EditorBrush>,
+    status: Res<EditorStatus>,
+    generator_level: Res<EditorGeneratorLevel>,
+    locale: Res<Locale>,
+) {
+    let on_off = |on: bool| locale.get(if on { "editor_on" } else { "editor_off" });
+
+    for mut text in &mut query {
+        text.sections[0].value = locale.format(
+            "editor_parameters",
+            &[
+                &level.0.ball_speed,
+                &level.0.lives,
+                &locale.get(match brush.0.brick_type {
+                    BrickType::Regular => "editor_regular",
+                    BrickType::Fireball => "editor_fireball",
+                    BrickType::Bomb => "editor_bomb",
+                }),
+                &locale.get(match brush.0.path {
+                    None => "editor_static",
+                    Some(BrickPath::Sweep { .. }) => "editor_sweeping",
+                    Some(BrickPath::Orbit { .. }) => "editor_orbiting",
+                }),
+                &generator_level.0,
+                &on_off(level.0.rules.descend_speed > 0.),
+                &on_off(level.0.rules.regrow_time.is_some()),
+                &on_off(level.0.rules.shrink_paddle),
+            ],
+        );
+        text.sections[1].value = format!("\n{}", status.0);
+    }
+}
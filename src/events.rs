@@ -0,0 +1,71 @@
+use crate::*;
+
+pub struct GameEventsPlugin;
+
+/// A ball left the paddle
+pub struct BallLaunchedEvent {
+    pub position: Vec3,
+}
+
+/// A ball fell out of the playfield, others may still be in play
+pub struct BallLostEvent {
+    pub position: Vec3,
+    pub ball_type: BallType,
+}
+
+/// All balls were lost and a spare one was put on the paddle
+pub struct LifeLostEvent {
+    pub lives_remaining: u8,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Powerup {
+    Fireball,
+}
+
+/// A power-up brick was hit and its effect started
+pub struct PowerupCollectedEvent {
+    pub powerup: Powerup,
+    pub position: Vec3,
+}
+
+/// A power-up effect ran out
+pub struct PowerupExpiredEvent {
+    pub powerup: Powerup,
+    pub position: Vec3,
+}
+
+/// The bricks of a level are in place and play begins
+pub struct LevelStartedEvent {
+    pub level: u16,
+}
+
+/// The last brick of a level was destroyed
+pub struct LevelClearedEvent {
+    pub level: u16,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameOverCause {
+    OutOfBalls,
+    BricksReachedPaddle,
+}
+
+pub struct GameOverEvent {
+    pub cause: GameOverCause,
+    pub level: u16,
+    pub score: f32,
+}
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BallLaunchedEvent>()
+            .add_event::<BallLostEvent>()
+            .add_event::<LifeLostEvent>()
+            .add_event::<PowerupCollectedEvent>()
+            .add_event::<PowerupExpiredEvent>()
+            .add_event::<LevelStartedEvent>()
+            .add_event::<LevelClearedEvent>()
+            .add_event::<GameOverEvent>();
+    }
+}
@@ -0,0 +1,239 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::*;
+
+const MAX_ROWS: usize = LEVEL_ROWS - 2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Shape {
+    Rectangle,
+    Pyramid,
+    Diamond,
+    Checker,
+    Stripes,
+    Frame,
+}
+
+const SHAPES: [Shape; 6] = [
+    Shape::Rectangle,
+    Shape::Pyramid,
+    Shape::Diamond,
+    Shape::Checker,
+    Shape::Stripes,
+    Shape::Frame,
+];
+
+/// Generates a brick layout for a level number from a seed.
+///
+/// The same seed and level always produce the same layout. Layouts are
+/// mirrored around the vertical center line, and both their size and their
+/// density grow with the level number.
+pub fn generate_level(seed: u64, level: u16) -> Level {
+    let mut rng = StdRng::seed_from_u64(seed ^ (level as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    let difficulty = level.saturating_sub(1) as f32;
+
+    let rows = (BRICK_ROWS + level.saturating_sub(1) as usize / 2).min(MAX_ROWS);
+    let density = (0.7 + difficulty * 0.03).min(0.95);
+    let fireball_ratio = (0.03 + difficulty * 0.005).min(0.08);
+    let bomb_ratio = (difficulty * 0.01 - 0.01).clamp(0., 0.06);
+    let mirror_vertically = level > 2 && rng.gen_bool(0.3);
+    let moving_row_ratio = (difficulty * 0.08 - 0.16).clamp(0., 0.5);
+
+    // The first level is always the familiar full rectangle
+    let shape = if level <= 1 {
+        Shape::Rectangle
+    } else {
+        SHAPES[rng.gen_range(0..SHAPES.len())]
+    };
+
+    let color_offset = rng.gen_range(0..BrickColor::ALL.len());
+    let half_columns = BRICK_COLUMNS.div_ceil(2);
+    let mut layout = Level {
+        ball_speed: 300. + level as f32 * 50.,
+        rules: level_rules(level),
+        ..default()
+    };
+
+    for y in 0..rows {
+        let path = if rng.gen_bool(moving_row_ratio as f64) {
+            let speed = 1. + difficulty * 0.05;
+
+            // Orbits only fit between the empty rows of stripes
+            Some(if shape == Shape::Stripes && rng.gen_bool(0.5) {
+                BrickPath::Orbit {
+                    radius: BRICK_HEIGHT / 2. - 2.,
+                    speed,
+                }
+            } else {
+                BrickPath::Sweep {
+                    amplitude: BRICK_WIDTH / 2.,
+                    speed: if y % 2 == 0 { speed } else { -speed },
+                }
+            })
+        } else {
+            None
+        };
+
+        for x in 0..half_columns {
+            let source_y = if mirror_vertically && y >= rows / 2 {
+                rows - 1 - y
+            } else {
+                y
+            };
+
+            if !shape_contains(shape, x, source_y, half_columns, rows) {
+                continue;
+            }
+
+            // Gaps are only punched after the first level
+            if level > 1 && !rng.gen_bool(density as f64) {
+                continue;
+            }
+
+            let brick = LevelBrick {
+                color: BrickColor::ALL[(source_y + color_offset) % BrickColor::ALL.len()],
+                brick_type: match rng.gen::<f32>() {
+                    roll if roll < fireball_ratio => BrickType::Fireball,
+                    roll if roll < fireball_ratio + bomb_ratio => BrickType::Bomb,
+                    _ => BrickType::Regular,
+                },
+                path,
+            };
+
+            layout.bricks[y][x] = Some(brick);
+            layout.bricks[y][BRICK_COLUMNS - 1 - x] = Some(brick);
+        }
+    }
+
+    // Never hand out an empty level
+    if layout.bricks.iter().flatten().all(Option::is_none) {
+        layout.bricks[0] = [Some(LevelBrick {
+            color: BrickColor::ALL[color_offset],
+            brick_type: BrickType::Regular,
+            path: None,
+        }); BRICK_COLUMNS];
+    }
+
+    layout
+}
+
+/// Later levels alternate between a descending field and regrowing bricks
+fn level_rules(level: u16) -> LevelRules {
+    let mut rules = LevelRules::default();
+
+    if level >= 6 && level % 3 == 0 {
+        rules.descend_speed = (level as f32 - 5.).min(8.) * 0.5;
+    } else if level >= 7 && level % 3 == 1 {
+        rules.regrow_time = Some((20. - level as f32 * 0.5).max(8.));
+    }
+
+    rules
+}
+
+/// Whether a cell in the left half of the grid is part of the shape
+fn shape_contains(shape: Shape, x: usize, y: usize, half_columns: usize, rows: usize) -> bool {
+    // Distance from the vertical center line, 0 for the innermost column
+    let from_center = half_columns - 1 - x;
+
+    match shape {
+        Shape::Rectangle => true,
+        Shape::Pyramid => from_center <= y * half_columns / rows,
+        Shape::Diamond => {
+            let from_middle = (y as i32 - rows as i32 / 2).unsigned_abs() as usize;
+            from_center + from_middle <= half_columns
+        }
+        Shape::Checker => (x + y) % 2 == 0,
+        Shape::Stripes => y % 2 == 0,
+        Shape::Frame => x == 0 || y == 0 || y == rows - 1 || from_center == 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brick_count(level: &Level) -> usize {
+        level
+            .bricks
+            .iter()
+            .flatten()
+            .filter(|cell| cell.is_some())
+            .count()
+    }
+
+    #[test]
+    fn same_seed_and_level_give_same_layout() {
+        for level in 1..20 {
+            assert_eq!(
+                generate_level(42, level).serialize(),
+                generate_level(42, level).serialize()
+            );
+        }
+    }
+
+    #[test]
+    fn layouts_are_mirrored() {
+        for seed in 0..20 {
+            let layout = generate_level(seed, 7);
+
+            for row in layout.bricks.iter() {
+                for x in 0..BRICK_COLUMNS {
+                    assert!(row[x] == row[BRICK_COLUMNS - 1 - x]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn layouts_are_never_empty() {
+        for seed in 0..50 {
+            for level in 1..30 {
+                assert!(brick_count(&generate_level(seed, level)) > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn first_level_is_a_full_rectangle() {
+        let layout = generate_level(1, 1);
+
+        assert_eq!(brick_count(&layout), BRICK_COLUMNS * BRICK_ROWS);
+    }
+
+    #[test]
+    fn early_levels_do_not_move() {
+        for seed in 0..50 {
+            for level in 1..4 {
+                let layout = generate_level(seed, level);
+
+                assert!(layout
+                    .bricks
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .all(|brick| brick.path.is_none()));
+            }
+        }
+    }
+
+    #[test]
+    fn early_levels_have_no_rules() {
+        for level in 1..6 {
+            assert_eq!(generate_level(3, level).rules, LevelRules::default());
+        }
+    }
+
+    #[test]
+    fn later_levels_have_more_bricks_on_average() {
+        const SEEDS: u64 = 100;
+
+        let average = |level| {
+            (0..SEEDS)
+                .map(|seed| brick_count(&generate_level(seed, level)))
+                .sum::<usize>() as f32
+                / SEEDS as f32
+        };
+
+        assert!(average(12) > average(2));
+    }
+}
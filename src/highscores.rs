@@ -0,0 +1,159 @@
+use crate::*;
+
+const HIGH_SCORES_SAVE: &str = "highscores";
+pub const MAX_HIGH_SCORES: usize = 10;
+
+pub struct HighScoresPlugin;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    pub level: u16,
+    /// Played with assists turned on
+    pub assisted: bool,
+}
+
+/// Best games on this machine across all profiles, highest first
+#[derive(Resource, Default)]
+pub struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+    pub fn parse(source: &str) -> Self {
+        let mut scores = HighScores::default();
+
+        for line in source.lines() {
+            let mut parts = line.splitn(3, ' ');
+            let (Some(score), Some(level), Some(name)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+
+            // Assisted games have a `*` after the level
+            let (level, assisted) = match level.strip_suffix('*') {
+                Some(level) => (level, true),
+                None => (level, false),
+            };
+
+            if let (Ok(score), Ok(level)) = (score.parse(), level.parse()) {
+                scores.insert(HighScore {
+                    name: name.to_string(),
+                    score,
+                    level,
+                    assisted,
+                });
+            }
+        }
+
+        scores
+    }
+
+    pub fn serialize(&self) -> String {
+        self.0
+            .iter()
+            .map(|entry| {
+                let flag = if entry.assisted { "*" } else { "" };
+                format!("{} {}{} {}\n", entry.score, entry.level, flag, entry.name)
+            })
+            .collect()
+    }
+
+    /// Adds a game to the table and returns its rank if it made the cut
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .0
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.0.len());
+
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        self.0.insert(rank, entry);
+        self.0.truncate(MAX_HIGH_SCORES);
+
+        Some(rank)
+    }
+}
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            read_save(HIGH_SCORES_SAVE)
+                .map(|source| HighScores::parse(&source))
+                .unwrap_or_default(),
+        )
+        .add_system(record_high_score);
+    }
+}
+
+fn record_high_score(
+    mut high_scores: ResMut<HighScores>,
+    mut game_over_events: EventReader<GameOverEvent>,
+    profiles: Res<Profiles>,
+    playtest: Res<EditorPlaytest>,
+    assists: Res<Assists>,
+) {
+    for event in game_over_events.iter() {
+        if playtest.0 || event.score <= 0. {
+            continue;
+        }
+
+        let entry = HighScore {
+            name: profiles.current_name().to_string(),
+            score: event.score.round() as u32,
+            level: event.level,
+            assisted: assists.is_active(),
+        };
+
+        if high_scores.insert(entry).is_some() {
+            if let Err(error) = write_save(HIGH_SCORES_SAVE, &high_scores.serialize()) {
+                warn!("Could not save high scores: {}", error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            level: 1,
+            assisted: false,
+        }
+    }
+
+    #[test]
+    fn table_stays_sorted_and_capped() {
+        let mut scores = HighScores::default();
+
+        for score in 0..MAX_HIGH_SCORES as u32 {
+            scores.insert(entry("low", score * 10 + 10));
+        }
+
+        assert_eq!(scores.insert(entry("best", 1000)), Some(0));
+        assert_eq!(scores.insert(entry("worst", 5)), None);
+        assert_eq!(scores.0.len(), MAX_HIGH_SCORES);
+        assert!(scores
+            .0
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[test]
+    fn high_scores_round_trip() {
+        let mut scores = HighScores::default();
+        scores.insert(entry("Ada Lovelace", 1200));
+        scores.insert(HighScore {
+            assisted: true,
+            ..entry("Linus", 800)
+        });
+
+        assert_eq!(HighScores::parse(&scores.serialize()).0, scores.0);
+    }
+}
@@ -6,32 +6,46 @@ pub struct GameInputPlugin;
 
 impl Plugin for GameInputPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(click_to_start.in_set(OnUpdate(GameState::Start)))
+        app.add_system(grab_cursor.in_schedule(OnEnter(GameState::Playing)))
             .add_systems((paddle_motion, launch_ball).in_set(OnUpdate(GameState::Playing)))
-            .add_system(window_focus);
+            .add_system(
+                window_focus
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Paused))),
+            );
     }
 }
 
 fn paddle_motion(
     mut motion_evr: EventReader<MouseMotion>,
-    mut q: Query<(&mut Transform, &mut Paddle)>,
+    level: Res<CurrentLevel>,
+    mut q: Query<(&mut Transform, &mut Paddle, &Collider)>,
 ) {
+    let bounds = level.0.bounds;
+
     for ev in motion_evr.iter() {
-        let (mut transform, mut paddle) = q.single_mut();
+        let (mut transform, mut paddle, collider) = q.single_mut();
         transform.translation.x += ev.delta.x;
 
         paddle.speed = ev.delta.x;
 
         transform.translation.x = transform.translation.x.clamp(
-            -WIN_WIDTH / 2. + PADDLE_WIDTH / 2.,
-            WIN_WIDTH / 2. - PADDLE_WIDTH / 2.,
+            bounds.left + collider.size.x / 2.,
+            bounds.right - collider.size.x / 2.,
         );
     }
 }
 
-fn click_to_start(btn: Res<Input<MouseButton>>, mut next_state: ResMut<NextState<GameState>>) {
-    if btn.just_pressed(MouseButton::Left) {
-        *next_state = NextState(Some(GameState::Playing));
+pub fn show_cursor(mut windows: Query<&mut Window>) {
+    for mut window in windows.iter_mut() {
+        window.cursor.grab_mode = CursorGrabMode::None;
+        window.cursor.visible = true;
+    }
+}
+
+fn grab_cursor(mut windows: Query<&mut Window>) {
+    for mut window in windows.iter_mut() {
+        window.cursor.grab_mode = CursorGrabMode::Confined;
+        window.cursor.visible = false;
     }
 }
 
@@ -62,12 +76,16 @@ fn window_focus(
 
 fn launch_ball(
     mut commands: Commands,
-    query: Query<Entity, With<AttachedToPaddle>>,
+    mut launched_events: EventWriter<BallLaunchedEvent>,
+    query: Query<(Entity, &Transform), With<AttachedToPaddle>>,
     kb: Res<Input<KeyCode>>,
 ) {
     if kb.just_pressed(KeyCode::Space) {
-        for entity in &query {
+        for (entity, transform) in &query {
             commands.entity(entity).remove::<AttachedToPaddle>();
+            launched_events.send(BallLaunchedEvent {
+                position: transform.translation,
+            });
         }
         // if let Ok(entity) = query.get_single() {
         //     commands.entity(entity).remove::<AttachedToPaddle>();
@@ -0,0 +1,295 @@
+use std::{fs, path::Path};
+
+use crate::*;
+
+pub const LEVEL_ROWS: usize = 12;
+pub const CUSTOM_LEVEL_PATH: &str = "assets/levels/custom.level";
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct LevelBrick {
+    pub color: BrickColor,
+    pub brick_type: BrickType,
+    pub path: Option<BrickPath>,
+}
+
+/// Movement of a brick around its grid position
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrickPath {
+    /// Horizontal back and forth, a negative speed starts moving left
+    Sweep { amplitude: f32, speed: f32 },
+    /// Circle around the grid position
+    Orbit { radius: f32, speed: f32 },
+}
+
+impl BrickPath {
+    /// Offset from the grid position after moving for `elapsed` seconds
+    pub fn offset(&self, elapsed: f32) -> Vec2 {
+        match *self {
+            BrickPath::Sweep { amplitude, speed } => {
+                Vec2::new(amplitude * (elapsed * speed).sin(), 0.)
+            }
+            BrickPath::Orbit { radius, speed } => {
+                Vec2::new((elapsed * speed).cos(), (elapsed * speed).sin()) * radius
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Level {
+    pub ball_speed: f32,
+    pub lives: u8,
+    pub rules: LevelRules,
+    pub bounds: ArenaBounds,
+    pub bricks: [[Option<LevelBrick>; BRICK_COLUMNS]; LEVEL_ROWS],
+}
+
+/// Mechanics that change the brick field while playing
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct LevelRules {
+    /// Speed in pixels per second at which the whole field moves down
+    pub descend_speed: f32,
+    /// Seconds after which a destroyed brick grows back
+    pub regrow_time: Option<f32>,
+    /// Halve the paddle the first time a ball reaches the ceiling
+    pub shrink_paddle: bool,
+}
+
+#[derive(Resource)]
+pub struct CurrentLevel(pub Level);
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            ball_speed: 350.,
+            lives: EXTRA_BALL_COUNT,
+            rules: LevelRules::default(),
+            bounds: ArenaBounds::default(),
+            bricks: [[None; BRICK_COLUMNS]; LEVEL_ROWS],
+        }
+    }
+}
+
+impl Level {
+    /// Parses the text level format:
+    ///
+    /// ```text
+    /// ball_speed: 350
+    /// lives: 3
+    /// descend_speed: 2
+    /// regrow_time: 10
+    /// shrink_paddle: true
+    /// bounds: -336 336 380
+    /// rrrrrrrrrr
+    /// ooOoo..ooo
+    /// path: 4 1 sweep 32 1.5
+    /// ```
+    ///
+    /// Each grid line is a brick row, lowercase letters are regular bricks,
+    /// uppercase letters are fireball bricks, `*` is a bomb and `.` is an
+    /// empty cell.
+    /// `path` lines give the brick at a column and row a `sweep` or `orbit`.
+    /// `bounds` moves the left wall, right wall and ceiling to these positions,
+    /// every brick has to stay inside them.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut level = Level::default();
+        let mut paths = vec![];
+        let mut y = 0;
+
+        for (line_number, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                let invalid = || format!("line {}: invalid value for {}", line_number + 1, key);
+
+                match key.trim() {
+                    "ball_speed" => level.ball_speed = value.parse().map_err(|_| invalid())?,
+                    "lives" => level.lives = value.parse().map_err(|_| invalid())?,
+                    "descend_speed" => {
+                        level.rules.descend_speed = value.parse().map_err(|_| invalid())?
+                    }
+                    "regrow_time" => {
+                        level.rules.regrow_time = Some(value.parse().map_err(|_| invalid())?)
+                    }
+                    "shrink_paddle" => {
+                        level.rules.shrink_paddle = value.parse().map_err(|_| invalid())?
+                    }
+                    "bounds" => level.bounds = ArenaBounds::parse(value).ok_or_else(invalid)?,
+                    "path" => paths.push((line_number, parse_path(value).ok_or_else(invalid)?)),
+                    key => return Err(format!("line {}: unknown key {}", line_number + 1, key)),
+                }
+                continue;
+            }
+
+            if y >= LEVEL_ROWS {
+                return Err(format!("more than {} brick rows", LEVEL_ROWS));
+            }
+
+            for (x, symbol) in line.chars().enumerate() {
+                if x >= BRICK_COLUMNS {
+                    return Err(format!(
+                        "line {}: more than {} columns",
+                        line_number + 1,
+                        BRICK_COLUMNS
+                    ));
+                }
+
+                level.bricks[y][x] = match symbol {
+                    '.' => None,
+                    '*' => Some(LevelBrick {
+                        color: BrickColor::Red,
+                        brick_type: BrickType::Bomb,
+                        path: None,
+                    }),
+                    symbol => {
+                        let color = BrickColor::from_symbol(symbol.to_ascii_lowercase()).ok_or(
+                            format!("line {}: unknown brick {}", line_number + 1, symbol),
+                        )?;
+
+                        Some(LevelBrick {
+                            color,
+                            brick_type: if symbol.is_uppercase() {
+                                BrickType::Fireball
+                            } else {
+                                BrickType::Regular
+                            },
+                            path: None,
+                        })
+                    }
+                };
+            }
+
+            y += 1;
+        }
+
+        for (line_number, (x, y, path)) in paths {
+            match level.bricks.get_mut(y).and_then(|row| row.get_mut(x)) {
+                Some(Some(brick)) => brick.path = Some(path),
+                _ => return Err(format!("line {}: no brick at {} {}", line_number + 1, x, y)),
+            }
+        }
+
+        // A brick behind a wall could never be hit and the level never cleared
+        for (y, row) in level.bricks.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if cell.is_some() && !level.bounds.holds_cell(x, y) {
+                    return Err(format!("brick at {} {} is outside the bounds", x, y));
+                }
+            }
+        }
+
+        Ok(level)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut output = format!("ball_speed: {}\nlives: {}\n", self.ball_speed, self.lives);
+
+        if self.rules.descend_speed > 0. {
+            output += &format!("descend_speed: {}\n", self.rules.descend_speed);
+        }
+
+        if let Some(regrow_time) = self.rules.regrow_time {
+            output += &format!("regrow_time: {}\n", regrow_time);
+        }
+
+        if self.rules.shrink_paddle {
+            output += "shrink_paddle: true\n";
+        }
+
+        if self.bounds != ArenaBounds::default() {
+            output += &format!("bounds: {}\n", self.bounds.serialize());
+        }
+
+        for row in self.bricks.iter() {
+            for cell in row.iter() {
+                output.push(match cell {
+                    None => '.',
+                    Some(brick) if brick.brick_type == BrickType::Bomb => '*',
+                    Some(brick) if brick.brick_type == BrickType::Fireball => {
+                        brick.color.symbol().to_ascii_uppercase()
+                    }
+                    Some(brick) => brick.color.symbol(),
+                });
+            }
+            output.push('\n');
+        }
+
+        for (y, row) in self.bricks.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                match cell.and_then(|brick| brick.path) {
+                    Some(BrickPath::Sweep { amplitude, speed }) => {
+                        output += &format!("path: {} {} sweep {} {}\n", x, y, amplitude, speed);
+                    }
+                    Some(BrickPath::Orbit { radius, speed }) => {
+                        output += &format!("path: {} {} orbit {} {}\n", x, y, radius, speed);
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        output
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        Level::parse(&source)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        if let Some(directory) = Path::new(path).parent() {
+            fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+
+        fs::write(path, self.serialize()).map_err(|e| e.to_string())
+    }
+}
+
+/// Parses `<column> <row> <sweep|orbit> <size> <speed>`
+fn parse_path(value: &str) -> Option<(usize, usize, BrickPath)> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [x, y, kind, size, speed] = parts[..] else {
+        return None;
+    };
+
+    let (size, speed) = (size.parse().ok()?, speed.parse().ok()?);
+    let path = match kind {
+        "sweep" => BrickPath::Sweep {
+            amplitude: size,
+            speed,
+        },
+        "orbit" => BrickPath::Orbit {
+            radius: size,
+            speed,
+        },
+        _ => return None,
+    };
+
+    Some((x.parse().ok()?, y.parse().ok()?, path))
+}
+
+pub fn brick_position(x: usize, y: usize) -> Vec3 {
+    Vec3::new(
+        x as f32 * BRICK_WIDTH - 4.5 * BRICK_WIDTH,
+        WIN_HEIGHT / 2. - y as f32 * BRICK_HEIGHT - 100.,
+        10.,
+    )
+}
+
+/// Grid cell under a world position, if any
+pub fn brick_cell(position: Vec2) -> Option<(usize, usize)> {
+    let x = ((position.x + 5. * BRICK_WIDTH) / BRICK_WIDTH).floor();
+    let y = ((WIN_HEIGHT / 2. - 100. + BRICK_HEIGHT / 2. - position.y) / BRICK_HEIGHT).floor();
+
+    if x < 0. || y < 0. || x >= BRICK_COLUMNS as f32 || y >= LEVEL_ROWS as f32 {
+        return None;
+    }
+
+    Some((x as usize, y as usize))
+}
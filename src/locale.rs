@@ -0,0 +1,287 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::*;
+
+const LANGUAGE_SAVE: &str = "language";
+const LOCALE_DIRECTORY: &str = "locale";
+const LOCALE_EXTENSION: &str = "lang";
+const DEFAULT_LANGUAGE: &str = "en";
+
+pub struct LocalePlugin;
+
+/// String table of one language, read from `<code>.lang` in the locale folder
+#[derive(Clone, Debug)]
+pub struct Language {
+    pub code: String,
+    strings: HashMap<String, String>,
+}
+
+impl Language {
+    pub fn parse(code: &str, source: &str) -> Self {
+        Language {
+            code: code.to_string(),
+            strings: parse_string_table(source),
+        }
+    }
+
+    /// Built in so the game always has text, even when the locale folder is missing
+    pub fn english() -> Self {
+        Language::parse(DEFAULT_LANGUAGE, include_str!("../assets/locale/en.lang"))
+    }
+
+    /// Name of the language in that language
+    pub fn name(&self) -> &str {
+        self.strings
+            .get("language_name")
+            .map(|name| name.as_str())
+            .unwrap_or(&self.code)
+    }
+
+    fn thousands_separator(&self) -> Option<char> {
+        self.strings
+            .get("thousands_separator")
+            .and_then(|separator| separator.chars().next())
+    }
+}
+
+/// Parses `key = value` lines, `#` starts a comment and `\n` in a value is a line break.
+/// Only ASCII whitespace is trimmed so a no-break space can be a value.
+pub fn parse_string_table(source: &str) -> HashMap<String, String> {
+    source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_string(),
+                value
+                    .trim_matches(|c: char| c.is_ascii_whitespace())
+                    .replace("\\n", "\n"),
+            )
+        })
+        .collect()
+}
+
+/// Installed languages and the selected one, missing strings fall back to English
+#[derive(Resource)]
+pub struct Locale {
+    pub installed: Vec<Language>,
+    pub current: usize,
+}
+
+impl Locale {
+    /// English plus every string table found in the locale folder
+    pub fn discover() -> Self {
+        let mut installed = vec![Language::english()];
+
+        for (code, source) in read_locale_files() {
+            if code == DEFAULT_LANGUAGE {
+                installed[0] = Language::parse(&code, &source);
+            } else {
+                installed.push(Language::parse(&code, &source));
+            }
+        }
+
+        installed[1..].sort_by(|a, b| a.code.cmp(&b.code));
+
+        Locale {
+            installed,
+            current: 0,
+        }
+    }
+
+    pub fn language(&self) -> &Language {
+        &self.installed[self.current]
+    }
+
+    pub fn select(&mut self, code: &str) {
+        self.current = self
+            .installed
+            .iter()
+            .position(|language| language.code == code.trim())
+            .unwrap_or(0);
+    }
+
+    pub fn next(&mut self) {
+        self.current = (self.current + 1) % self.installed.len();
+    }
+
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.language()
+            .strings
+            .get(key)
+            .or_else(|| self.installed[0].strings.get(key))
+            .map(|value| value.as_str())
+            .unwrap_or(key)
+    }
+
+    /// Fills the `{}` placeholders of a string in order
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        let mut parts = self.get(key).split("{}");
+        let mut args = args.iter();
+        let mut result = parts.next().unwrap_or_default().to_string();
+
+        for part in parts {
+            if let Some(arg) = args.next() {
+                result.push_str(&arg.to_string());
+            }
+            result.push_str(part);
+        }
+
+        result
+    }
+
+    /// Rounded number with the thousands separator of the language
+    pub fn number(&self, value: f32) -> String {
+        let separator = self
+            .language()
+            .thousands_separator()
+            .or_else(|| self.installed[0].thousands_separator())
+            .unwrap_or(',');
+        let digits = value.round().abs().to_string();
+        let mut result = String::new();
+
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                result.push(separator);
+            }
+            result.push(digit);
+        }
+
+        if value.round() < 0. {
+            result.insert(0, '-');
+        }
+
+        result
+    }
+
+    /// Whole minutes and seconds
+    pub fn duration(&self, seconds: f32) -> String {
+        let seconds = seconds.max(0.) as u32;
+
+        self.format(
+            "format_duration",
+            &[&(seconds / 60), &format!("{:02}", seconds % 60)],
+        )
+    }
+
+    /// A ratio from 0 to 1 as a rounded percentage
+    pub fn percent(&self, ratio: f32) -> String {
+        self.format("format_percent", &[&(ratio * 100.).round()])
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = write_save(LANGUAGE_SAVE, &self.language().code) {
+            warn!("Could not save language: {}", error);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_locale_files() -> Vec<(String, String)> {
+    use std::fs;
+
+    let directory = bevy::asset::FileAssetIo::get_base_path()
+        .join("assets")
+        .join(LOCALE_DIRECTORY);
+
+    let Ok(entries) = fs::read_dir(directory) else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == LOCALE_EXTENSION))
+        .filter_map(|path| {
+            let code = path.file_stem()?.to_str()?.to_string();
+            let source = fs::read_to_string(&path).ok()?;
+
+            Some((code, source))
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_locale_files() -> Vec<(String, String)> {
+    vec![]
+}
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        let mut locale = Locale::discover();
+
+        if let Ok(code) = read_save(LANGUAGE_SAVE) {
+            locale.select(&code);
+        }
+
+        app.insert_resource(locale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selected(code: &str) -> Locale {
+        let mut locale = Locale::discover();
+        locale.select(code);
+        locale
+    }
+
+    #[test]
+    fn every_language_has_every_string() {
+        let locale = Locale::discover();
+        let english = &locale.installed[0];
+
+        assert_eq!(
+            locale
+                .installed
+                .iter()
+                .map(|language| language.code.as_str())
+                .collect::<Vec<_>>(),
+            ["en", "de", "es", "fr"]
+        );
+
+        for language in &locale.installed {
+            for key in english.strings.keys() {
+                assert!(
+                    language.strings.contains_key(key),
+                    "{} is missing {}",
+                    language.code,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn missing_strings_fall_back() {
+        let locale = selected("de");
+
+        assert_eq!(locale.get("menu_play"), "Spielen");
+        assert_eq!(locale.get("no_such_key"), "no_such_key");
+        assert_eq!(selected("xx").language().code, "en");
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_order() {
+        let locale = selected("en");
+
+        assert_eq!(
+            locale.format("menu_continue", &[&7]),
+            "Continue from level 7"
+        );
+    }
+
+    #[test]
+    fn numbers_use_language_formats() {
+        assert_eq!(selected("en").number(1234567.), "1,234,567");
+        assert_eq!(selected("de").number(12345.4), "12.345");
+        assert_eq!(selected("fr").number(12345.), "12\u{a0}345");
+        assert_eq!(selected("en").number(-2500.), "-2,500");
+        assert_eq!(selected("en").duration(75.), "1:15");
+        assert_eq!(selected("en").percent(0.5), "50%");
+        assert_eq!(selected("fr").percent(0.5), "50\u{a0}%");
+    }
+}
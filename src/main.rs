@@ -1,51 +1,125 @@
 use std::time::Duration;
 
+use achievements::*;
+use animation::*;
+use arena::*;
 use assets::*;
+use assist::*;
+use audio::*;
 use ball::*;
-use bevy::{
-    prelude::*,
-    sprite::collide_aabb::*,
-    window::{WindowFocused, WindowResolution},
-};
+use bevy::{prelude::*, sprite::collide_aabb::*, window::WindowFocused};
+use camera::*;
+use editor::*;
+use events::*;
+use generator::*;
+use highscores::*;
 use input::*;
+use level::*;
+use locale::*;
+use menu::*;
+use palette::*;
+use particles::*;
+use profile::*;
 use rand::Rng;
+use scoring::*;
+use stats::*;
+use storage::*;
+use theme::*;
 use ui::*;
 
 const WIN_WIDTH: f32 = 800.;
 const WIN_HEIGHT: f32 = 800.;
 const PADDLE_WIDTH: f32 = 104.;
 const PADDLE_HEIGHT: f32 = 24.;
+const PADDLE_Y: f32 = -280.;
 const BRICK_WIDTH: f32 = 64.;
 const BRICK_HEIGHT: f32 = 32.;
 const BG_COLOR: Color = Color::rgb(0.218, 0.554, 0.777);
-const BASE_BRICK_SCORE: f32 = 10.;
-const SCORE_MULTIPLIER_TIMEOUT: f32 = 1.;
-const SCORE_MULTIPLIER: f32 = 50.;
 const SCORE_ANIM_MAX_DURATION: f32 = 0.6;
 const EXTRA_BALL_COUNT: u8 = 3;
 const BALLS_SPEED_TIME_INCREMENT: f32 = 2.;
 const BRICK_COLUMNS: usize = 10;
 const BRICK_ROWS: usize = 6;
-const MAX_FIREBALLS: u8 = 4;
 const MAX_FIREBALL_AGE: f32 = 4.;
+const BOMB_RADIUS: f32 = 1.6 * BRICK_WIDTH;
 
+mod achievements;
+mod animation;
+mod arena;
 mod assets;
+mod assist;
+mod audio;
 mod ball;
+mod camera;
+mod editor;
+mod events;
+mod generator;
+mod highscores;
 mod input;
+mod level;
+mod locale;
+mod menu;
+mod palette;
+mod particles;
+mod profile;
+mod scoring;
+mod stats;
+mod storage;
+mod theme;
 mod ui;
 
 pub struct BrickDesctructionEvent {
+    brick: Entity,
     position: Vec3,
     brick_type: BrickType,
+    color: BrickColor,
+    /// Column and row in the level layout
+    cell: (usize, usize),
+    /// Number of bomb explosions that led to this brick, 0 for direct hits
+    chain_depth: u8,
+    /// Ball that hit the brick, or the bomb that set off its chain
+    ball: Entity,
+    ball_type: BallType,
+    /// Points away from the brick, towards the ball or out of the bomb blast
+    normal: Vec2,
+    /// Speed of the ball at impact
+    speed: f32,
 }
 
-pub struct ScoreIncrementEvent(f32);
+pub struct ScoreIncrementEvent {
+    amount: f32,
+    /// Part of the amount that came from combo and chain bonuses
+    bonus: f32,
+    position: Vec3,
+}
 
 #[derive(Component)]
 pub struct Brick {
     brick_type: BrickType,
+    color: BrickColor,
+    cell: (usize, usize),
+}
+
+#[derive(Component)]
+pub struct MovingBrick {
+    pub path: BrickPath,
+    pub origin: Vec3,
+    pub velocity: Vec2,
+}
+
+/// Shared state of all bricks in the current level
+#[derive(Resource, Default)]
+pub struct BrickField {
+    /// Distance the whole field has descended
+    pub descent: f32,
+    /// Time moving bricks have been moving
+    pub elapsed: f32,
 }
 
+/// Destroyed bricks waiting to grow back
+#[derive(Resource, Default)]
+pub struct RegrowingBricks(Vec<((usize, usize), Timer)>);
+
 #[derive(Component)]
 pub struct Paddle {
     pub speed: f32,
@@ -56,10 +130,72 @@ pub struct Collider {
     pub size: Vec2,
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum BrickType {
     Regular,
     Fireball,
+    Bomb,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum BrickColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    LightGreen,
+    Blue,
+}
+
+impl BrickColor {
+    pub const ALL: [BrickColor; 6] = [
+        BrickColor::Red,
+        BrickColor::Orange,
+        BrickColor::Yellow,
+        BrickColor::Green,
+        BrickColor::LightGreen,
+        BrickColor::Blue,
+    ];
+
+    pub fn texture(&self, images: &ImageHandles) -> Handle<Image> {
+        match self {
+            BrickColor::Red => images.brick_red.clone(),
+            BrickColor::Orange => images.brick_orange.clone(),
+            BrickColor::Yellow => images.brick_yellow.clone(),
+            BrickColor::Green => images.brick_green.clone(),
+            BrickColor::LightGreen => images.brick_light_green.clone(),
+            BrickColor::Blue => images.brick_blue.clone(),
+        }
+    }
+
+    /// Approximate colour of the texture, for effects
+    pub fn color(&self) -> Color {
+        match self {
+            BrickColor::Red => Color::rgb(0.9, 0.2, 0.2),
+            BrickColor::Orange => Color::rgb(1., 0.55, 0.1),
+            BrickColor::Yellow => Color::rgb(1., 0.9, 0.2),
+            BrickColor::Green => Color::rgb(0.2, 0.7, 0.2),
+            BrickColor::LightGreen => Color::rgb(0.6, 0.95, 0.4),
+            BrickColor::Blue => Color::rgb(0.2, 0.4, 0.95),
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            BrickColor::Red => 'r',
+            BrickColor::Orange => 'o',
+            BrickColor::Yellow => 'y',
+            BrickColor::Green => 'g',
+            BrickColor::LightGreen => 'l',
+            BrickColor::Blue => 'b',
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        BrickColor::ALL
+            .into_iter()
+            .find(|color| color.symbol() == symbol)
+    }
 }
 
 #[derive(Resource)]
@@ -68,6 +204,7 @@ pub struct PlayerProgress {
     extra_balls_remaining: u8,
     level: u16,
     bonus_score: f32,
+    seed: u64,
 }
 
 impl Default for PlayerProgress {
@@ -77,6 +214,7 @@ impl Default for PlayerProgress {
             extra_balls_remaining: EXTRA_BALL_COUNT,
             level: 1,
             bonus_score: 0.,
+            seed: rand::random(),
         }
     }
 }
@@ -84,23 +222,20 @@ impl Default for PlayerProgress {
 #[derive(States, Hash, Clone, PartialEq, Eq, Debug, Default)]
 enum GameState {
     #[default]
+    Loading,
     Start,
     Playing,
     LevelCompleted,
     Paused,
     GameOver,
+    Editor,
+    Profile,
 }
 
 pub struct GamePauseEvent {
     pub should_pause: bool,
 }
 
-#[derive(Resource)]
-struct ScoreIncrementTimer(pub Timer);
-
-#[derive(Resource)]
-pub struct StateTransitionTimer(pub Timer);
-
 fn main() {
     let mut app = App::new();
 
@@ -108,22 +243,34 @@ fn main() {
 
     // Resources
     app.insert_resource(PlayerProgress::default())
-        .insert_resource(StateTransitionTimer(Timer::new(
-            Duration::from_secs(2),
-            TimerMode::Once,
-        )))
-        .insert_resource(ScoreIncrementTimer(Timer::new(
-            Duration::from_secs_f32(1.),
-            TimerMode::Once,
-        )))
+        .insert_resource(CurrentLevel(Level::default()))
+        .insert_resource(BrickField::default())
+        .insert_resource(RegrowingBricks::default())
         .insert_resource(ClearColor(BG_COLOR));
 
     // Plugins
     app.add_plugin(GameAssetsPlugin)
+        .add_plugin(GameEventsPlugin)
         .add_plugins(DefaultPlugins)
         .add_plugin(UiPlugin)
         .add_plugin(BallPlugin)
-        .add_plugin(GameInputPlugin);
+        .add_plugin(GameInputPlugin)
+        .add_plugin(EditorPlugin)
+        .add_plugin(ParticlePlugin)
+        .add_plugin(CameraEffectsPlugin)
+        .add_plugin(ScoringPlugin)
+        .add_plugin(StatsPlugin)
+        .add_plugin(AchievementsPlugin)
+        .add_plugin(ProfilePlugin)
+        .add_plugin(HighScoresPlugin)
+        .add_plugin(MenuPlugin)
+        .add_plugin(LocalePlugin)
+        .add_plugin(PalettePlugin)
+        .add_plugin(AssistPlugin)
+        .add_plugin(ThemePlugin)
+        .add_plugin(AnimationPlugin)
+        .add_plugin(GameAudioPlugin)
+        .add_plugin(ArenaPlugin);
 
     // Events
     app.add_event::<GamePauseEvent>()
@@ -133,17 +280,17 @@ fn main() {
     // State independent systems
     app.add_startup_system(spawn_camera)
         .add_startup_system(configure_window)
-        .add_system(on_window_focus)
         // .add_system(on_pause)
-        // .add_startup_system(play_music)
-        .add_system(play_sounds);
+        .add_system(on_window_focus);
 
     // Playing state
     app.add_systems(
-        (reset_bonus_score, spawn_paddle, spawn_ball).in_schedule(OnEnter(GameState::Playing)),
+        (spawn_paddle, spawn_ball, announce_level_start)
+            .after(load_level)
+            .in_schedule(OnEnter(GameState::Playing)),
     )
     .add_systems(
-        (spawn_bricks, apply_system_buffers, inject_fireballs)
+        (load_level, spawn_bricks)
             .chain()
             .in_schedule(OnEnter(GameState::Playing)),
     )
@@ -151,8 +298,10 @@ fn main() {
         (
             on_all_balls_lost.after(ball_loss),
             next_level,
+            move_bricks.before(check_collisions),
+            descend_bricks.before(move_bricks),
+            regrow_bricks,
             trigger_powerup,
-            update_score,
         )
             .in_set(OnUpdate(GameState::Playing)),
     )
@@ -166,14 +315,8 @@ fn main() {
             .in_schedule(OnExit(GameState::Playing)),
     );
 
-    // Level transition state
-    app.add_system(transition_timer.in_set(OnUpdate(GameState::LevelCompleted)));
-
     // GameOver state
-    app.add_systems((
-        transition_timer.in_set(OnUpdate(GameState::GameOver)),
-        reset_player_progress.in_schedule(OnExit(GameState::GameOver)),
-    ));
+    app.add_system(reset_player_progress.in_schedule(OnExit(GameState::GameOver)));
 
     app.run();
 }
@@ -186,185 +329,209 @@ fn configure_window(mut query: Query<&mut Window>) {
     }
 }
 
-fn play_music(assets: Res<GameAssets>, audio: Res<Audio>) {
-    audio.play_with_settings(
-        assets.audio.music_01.clone(),
-        PlaybackSettings {
-            repeat: true,
-            volume: 0.5,
-            speed: 1.,
-        },
-    );
-}
-
-fn reset_bonus_score(mut progress: ResMut<PlayerProgress>) {
-    progress.bonus_score = 0.;
-}
-
-fn transition_timer(
-    mut timer: ResMut<StateTransitionTimer>,
-    state: ResMut<State<GameState>>,
-    mut next_state: ResMut<NextState<GameState>>,
-    time: Res<Time>,
-) {
-    timer.0.tick(time.delta());
-
-    if timer.0.just_finished() {
-        timer.0.reset();
-        if state.0 == GameState::GameOver {
-            *next_state = NextState(Some(GameState::Start));
-        } else if state.0 == GameState::LevelCompleted {
-            *next_state = NextState(Some(GameState::Playing));
-        }
-    }
-}
-
 fn reset_player_progress(mut player_progress: ResMut<PlayerProgress>) {
     *player_progress = PlayerProgress::default();
 }
 
-fn inject_fireballs(
-    mut commands: Commands,
-    mut query: Query<(Entity, &mut Brick)>,
-    assets: Res<GameAssets>,
+fn load_level(
+    mut current_level: ResMut<CurrentLevel>,
+    mut brick_field: ResMut<BrickField>,
+    mut regrowing_bricks: ResMut<RegrowingBricks>,
+    mut progress: ResMut<PlayerProgress>,
+    playtest: Res<EditorPlaytest>,
+    editor_level: Res<EditorLevel>,
 ) {
-    let fireball_bundle = SpriteBundle {
-        texture: assets.image.ball_fire.clone(),
-        transform: Transform::from_xyz(0., 0., 10.),
-        ..default()
+    current_level.0 = if playtest.0 {
+        editor_level.0.clone()
+    } else {
+        generate_level(progress.seed, progress.level)
     };
 
-    let mut rng = rand::thread_rng();
-    let mut indices: Vec<usize> = vec![];
+    progress.extra_balls_remaining = current_level.0.lives;
+    *brick_field = BrickField::default();
+    regrowing_bricks.0.clear();
+}
 
-    for _ in 0..MAX_FIREBALLS {
-        indices.push(rng.gen_range(0..BRICK_COLUMNS * BRICK_ROWS));
+fn spawn_bricks(mut commands: Commands, sheet: Res<SpriteSheet>, level: Res<CurrentLevel>) {
+    for (y, row) in level.0.bricks.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if let Some(level_brick) = cell {
+                spawn_brick(
+                    &mut commands,
+                    &sheet,
+                    level_brick,
+                    (x, y),
+                    brick_position(x, y),
+                );
+            }
+        }
     }
+}
 
-    let mut i = 0;
-
-    for (brick_entity, mut brick) in query.iter_mut() {
-        if indices.contains(&i) {
-            let child_entity = commands.spawn(fireball_bundle.clone()).id();
-            commands.entity(brick_entity).add_child(child_entity);
+pub fn spawn_brick(
+    commands: &mut Commands,
+    sheet: &SpriteSheet,
+    level_brick: &LevelBrick,
+    cell: (usize, usize),
+    position: Vec3,
+) -> Entity {
+    let mut brick = commands.spawn((
+        Brick {
+            brick_type: level_brick.brick_type,
+            color: level_brick.color,
+            cell,
+        },
+        sheet.bundle(
+            sheet.brick(level_brick.brick_type, level_brick.color),
+            Transform::from_translation(position),
+        ),
+        Collider {
+            size: Vec2::new(BRICK_WIDTH, BRICK_HEIGHT),
+        },
+    ));
 
-            brick.brick_type = BrickType::Fireball;
-        }
+    if let Some(path) = level_brick.path {
+        brick.insert(MovingBrick {
+            path,
+            origin: position,
+            velocity: Vec2::ZERO,
+        });
+    }
 
-        i += 1;
+    if level_brick.brick_type == BrickType::Fireball {
+        brick.with_children(|parent| {
+            parent.spawn((
+                sheet.bundle(&sheet.ball_fire, Transform::from_xyz(0., 0., 10.)),
+                SpriteAnimation::flicker(&sheet.ball_fire),
+            ));
+        });
     }
+
+    brick.id()
 }
 
-fn spawn_bricks(mut commands: Commands, assets: Res<GameAssets>) {
-    for x in 0..BRICK_COLUMNS {
-        for y in 0..BRICK_ROWS {
-            let brick_sprites = [
-                &assets.image.brick_red,
-                &assets.image.brick_orange,
-                &assets.image.brick_yellow,
-                &assets.image.brick_green,
-                &assets.image.brick_light_green,
-                &assets.image.brick_blue,
-            ];
+fn move_bricks(
+    time: Res<Time>,
+    mut brick_field: ResMut<BrickField>,
+    mut query: Query<(&mut MovingBrick, &mut Transform)>,
+) {
+    let delta = time.delta_seconds();
 
-            commands.spawn((
-                Brick {
-                    brick_type: BrickType::Regular,
-                },
-                SpriteBundle {
-                    texture: brick_sprites[y].clone(),
-                    transform: Transform::from_xyz(
-                        x as f32 * BRICK_WIDTH - 4.5 * BRICK_WIDTH,
-                        WIN_HEIGHT / 2. - y as f32 * BRICK_HEIGHT - 100.,
-                        10.,
-                    ),
-                    ..default()
-                },
-                Collider {
-                    size: Vec2::new(BRICK_WIDTH, BRICK_HEIGHT),
-                },
-            ));
-        }
+    if delta <= 0. {
+        return;
     }
-}
 
-fn spawn_camera(mut commands: Commands) {
-    commands.spawn(Camera2dBundle::default());
-}
+    let previous = brick_field.elapsed;
+    brick_field.elapsed += delta;
 
-fn spawn_paddle(mut commands: Commands, assets: Res<GameAssets>) {
-    commands
-        .spawn(Paddle { speed: 0. })
-        .insert(SpriteBundle {
-            transform: Transform::from_xyz(0., -280., 10.),
-            texture: assets.image.paddle.clone(),
-            ..default()
-        })
-        .insert(Collider {
-            size: Vec2::new(PADDLE_WIDTH, PADDLE_HEIGHT),
-        });
+    for (mut brick, mut transform) in query.iter_mut() {
+        let offset = brick.path.offset(brick_field.elapsed);
+        // Taken from the path rather than the transform, bricks spawn on their grid cell
+        // and would otherwise jump onto the path with a huge velocity in their first frame
+        brick.velocity = (offset - brick.path.offset(previous)) / delta;
+        transform.translation = brick.origin + offset.extend(0.);
+    }
 }
-fn play_sounds(
-    mut collision_events: EventReader<BallCollisionEvent>,
-    mut brick_destruction_events: EventReader<BrickDesctructionEvent>,
-    audio: Res<Audio>,
-    assets: Res<GameAssets>,
+
+fn descend_bricks(
+    time: Res<Time>,
+    level: Res<CurrentLevel>,
+    mut brick_field: ResMut<BrickField>,
+    mut query: Query<(&mut Transform, Option<&mut MovingBrick>), With<Brick>>,
+    mut game_over_events: EventWriter<GameOverEvent>,
+    progress: Res<PlayerProgress>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for _ in brick_destruction_events.iter() {
-        audio.play_with_settings(
-            assets.audio.drop_004.clone(),
-            PlaybackSettings {
-                repeat: false,
-                volume: 1.,
-                speed: rand::random::<f32>() * 0.4 + 0.8,
-            },
-        );
+    let descent = level.0.rules.descend_speed * time.delta_seconds();
+
+    if descent <= 0. {
+        return;
     }
 
-    for event in collision_events.iter() {
-        match event.0 {
-            BallCollisionType::Paddle => {
-                audio.play(assets.audio.drop_002.clone());
-            }
-            BallCollisionType::Wall => {
-                audio.play_with_settings(
-                    assets.audio.drop_003.clone(),
-                    PlaybackSettings {
-                        volume: 0.3,
-                        ..default()
-                    },
-                );
-            }
+    brick_field.descent += descent;
+
+    let mut reached_paddle = false;
+
+    for (mut transform, moving_brick) in query.iter_mut() {
+        if let Some(mut moving_brick) = moving_brick {
+            moving_brick.origin.y -= descent;
+        } else {
+            transform.translation.y -= descent;
         }
+
+        reached_paddle |=
+            transform.translation.y - BRICK_HEIGHT / 2. <= PADDLE_Y + PADDLE_HEIGHT / 2.;
+    }
+
+    if reached_paddle {
+        game_over_events.send(GameOverEvent {
+            cause: GameOverCause::BricksReachedPaddle,
+            level: progress.level,
+            score: progress.score,
+        });
+        *next_state = NextState(Some(GameState::GameOver));
     }
 }
 
-fn update_score(
+#[allow(clippy::too_many_arguments)]
+fn regrow_bricks(
+    mut commands: Commands,
     mut destruction_events: EventReader<BrickDesctructionEvent>,
-    mut score_events: EventWriter<ScoreIncrementEvent>,
-    mut player_progress: ResMut<PlayerProgress>,
-    mut timer: ResMut<ScoreIncrementTimer>,
+    mut regrowing_bricks: ResMut<RegrowingBricks>,
+    mut remaining_bricks: ResMut<RemainingBricks>,
+    level: Res<CurrentLevel>,
+    brick_field: Res<BrickField>,
+    sheet: Res<SpriteSheet>,
     time: Res<Time>,
 ) {
-    timer.0.tick(time.delta());
+    let Some(regrow_time) = level.0.rules.regrow_time else {
+        return;
+    };
 
-    for _ in destruction_events.iter() {
-        let mut score_increment = BASE_BRICK_SCORE;
+    for event in destruction_events.iter() {
+        regrowing_bricks.0.push((
+            event.cell,
+            Timer::from_seconds(regrow_time, TimerMode::Once),
+        ));
+    }
+
+    for ((x, y), timer) in regrowing_bricks.0.iter_mut() {
+        timer.tick(time.delta());
 
-        if !timer.0.finished() {
-            let bonus = (SCORE_MULTIPLIER_TIMEOUT - timer.0.elapsed_secs()) * SCORE_MULTIPLIER;
-            score_increment += bonus;
-            player_progress.bonus_score += bonus;
+        if let (true, Some(level_brick)) = (timer.just_finished(), &level.0.bricks[*y][*x]) {
+            let position = brick_position(*x, *y) - Vec3::Y * brick_field.descent;
+            spawn_brick(&mut commands, &sheet, level_brick, (*x, *y), position);
+            remaining_bricks.add((*x, *y));
         }
-        score_events.send(ScoreIncrementEvent(score_increment));
-        player_progress.score += score_increment;
-        timer.0.reset();
     }
+
+    regrowing_bricks.0.retain(|(_, timer)| !timer.finished());
 }
 
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn spawn_paddle(mut commands: Commands, sheet: Res<SpriteSheet>, assists: Res<Assists>) {
+    commands
+        .spawn(Paddle { speed: 0. })
+        .insert(sheet.bundle(
+            &sheet.paddle,
+            Transform::from_xyz(0., PADDLE_Y, 10.).with_scale(Vec3::new(
+                assists.paddle_width,
+                1.,
+                1.,
+            )),
+        ))
+        .insert(Collider {
+            size: Vec2::new(PADDLE_WIDTH * assists.paddle_width, PADDLE_HEIGHT),
+        });
+}
 fn on_all_balls_lost(
     mut commands: Commands,
     mut ball_loss_events: EventReader<AllBallsLostEvent>,
+    mut life_lost_events: EventWriter<LifeLostEvent>,
+    mut game_over_events: EventWriter<GameOverEvent>,
     mut player_progress: ResMut<PlayerProgress>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
@@ -372,7 +539,15 @@ fn on_all_balls_lost(
         if player_progress.extra_balls_remaining > 0 {
             player_progress.extra_balls_remaining -= 1;
             commands.add(SpawnBallCommand);
+            life_lost_events.send(LifeLostEvent {
+                lives_remaining: player_progress.extra_balls_remaining,
+            });
         } else {
+            game_over_events.send(GameOverEvent {
+                cause: GameOverCause::OutOfBalls,
+                level: player_progress.level,
+                score: player_progress.score,
+            });
             *next_state = NextState(Some(GameState::GameOver));
         }
     }
@@ -384,14 +559,26 @@ fn despawn<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>
     }
 }
 
+fn announce_level_start(
+    progress: Res<PlayerProgress>,
+    mut started_events: EventWriter<LevelStartedEvent>,
+) {
+    started_events.send(LevelStartedEvent {
+        level: progress.level,
+    });
+}
+
 fn next_level(
     query: Query<&Brick>,
     mut progress: ResMut<PlayerProgress>,
+    mut cleared_events: EventWriter<LevelClearedEvent>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     if query.is_empty() {
+        cleared_events.send(LevelClearedEvent {
+            level: progress.level,
+        });
         progress.level += 1;
-        progress.extra_balls_remaining = EXTRA_BALL_COUNT;
         *next_state = NextState(Some(GameState::LevelCompleted));
     }
 }
@@ -407,6 +594,7 @@ fn on_window_focus(
     }
 }
 
+#[allow(dead_code)]
 fn on_pause(
     mut pause_event: EventReader<GamePauseEvent>,
     state: ResMut<State<GameState>>,
@@ -426,10 +614,15 @@ fn on_pause(
 fn trigger_powerup(
     mut commands: Commands,
     mut events: EventReader<BrickDesctructionEvent>,
-    assets: Res<GameAssets>,
+    mut powerup_events: EventWriter<PowerupCollectedEvent>,
+    sheet: Res<SpriteSheet>,
 ) {
     for event in events.iter() {
         if event.brick_type == BrickType::Fireball {
+            powerup_events.send(PowerupCollectedEvent {
+                powerup: Powerup::Fireball,
+                position: event.position,
+            });
             commands.spawn((
                 Ball {
                     direction: Vec2::new(rand::random::<f32>(), rand::random::<f32>()),
@@ -437,15 +630,16 @@ fn trigger_powerup(
                     curve: 0.,
                     ball_type: BallType::FireBall,
                 },
-                SpriteBundle {
-                    texture: assets.image.ball_fire.clone(),
-                    transform: Transform::from_translation(event.position),
-                    ..default()
-                },
+                sheet.bundle(
+                    &sheet.ball_fire,
+                    Transform::from_translation(event.position),
+                ),
+                SpriteAnimation::flicker(&sheet.ball_fire),
                 Collider {
                     size: Vec2::splat(BALL_SIZE),
                 },
                 FireBall { age: 0. },
+                ParticleSpawner::flame_trail(),
             ));
         }
     }
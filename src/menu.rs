@@ -0,0 +1,661 @@
+use bevy::app::AppExit;
+
+use crate::*;
+
+const BUTTON_WIDTH: f32 = 320.;
+const BUTTON_HEIGHT: f32 = 48.;
+const BUTTON_COLOR: Color = Color::rgba(0., 0., 0., 0.5);
+const BUTTON_FOCUS_COLOR: Color = Color::rgba(1., 0.84, 0., 0.8);
+
+pub struct MenuPlugin;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MenuScreen {
+    Main,
+    Settings,
+    Assists,
+    HighScores,
+    LevelComplete,
+    GameOver,
+    Profile,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MenuAction {
+    Play,
+    Continue,
+    Open(MenuScreen),
+    PlayerStats,
+    LevelEditor,
+    Quit,
+    ToggleScreenEffects,
+    CycleLanguage,
+    CyclePalette,
+    CycleTheme,
+    CycleGameSpeed,
+    CyclePaddleWidth,
+    ToggleSafetyFloor,
+    NextLevel,
+    MainMenu,
+    PreviousProfile,
+    NextProfile,
+    NewProfile,
+    Back,
+}
+
+/// States that show a menu, each starts its own screen stack
+const MENU_STATES: [GameState; 4] = [
+    GameState::Start,
+    GameState::LevelCompleted,
+    GameState::GameOver,
+    GameState::Profile,
+];
+
+/// Screens opened on top of each other, the last one is shown
+#[derive(Resource)]
+pub struct MenuStack(pub Vec<MenuScreen>);
+
+/// Index of the focused button on the shown screen
+#[derive(Resource, Default)]
+pub struct MenuFocus(pub usize);
+
+pub struct MenuActionEvent(pub MenuAction);
+
+/// Why the last game ended, for the game over screen
+#[derive(Resource)]
+pub struct LastGameOver(pub GameOverCause);
+
+#[derive(Component)]
+pub struct MenuRoot;
+
+#[derive(Component)]
+pub struct MenuButton {
+    pub index: usize,
+    pub action: MenuAction,
+}
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MenuStack(vec![MenuScreen::Main]))
+            .insert_resource(MenuFocus::default())
+            .insert_resource(LastGameOver(GameOverCause::OutOfBalls))
+            .add_event::<MenuActionEvent>();
+
+        for state in MENU_STATES {
+            app.add_systems((show_cursor, reset_menu).in_schedule(OnEnter(state.clone())))
+                .add_system(despawn::<MenuRoot>.in_schedule(OnExit(state)));
+        }
+
+        app.add_system(remember_game_over.before(spawn_menu))
+            .add_systems(
+                (
+                    spawn_menu.run_if(resource_changed::<MenuStack>()),
+                    navigate_menu.after(spawn_menu),
+                    handle_menu_actions.after(navigate_menu),
+                    highlight_focused_button.after(navigate_menu),
+                )
+                    .distributive_run_if(menu_shown),
+            );
+    }
+}
+
+impl MenuScreen {
+    /// Bottom of the screen stack in a state
+    fn root(state: &GameState) -> Option<MenuScreen> {
+        match state {
+            GameState::Start => Some(MenuScreen::Main),
+            GameState::LevelCompleted => Some(MenuScreen::LevelComplete),
+            GameState::GameOver => Some(MenuScreen::GameOver),
+            GameState::Profile => Some(MenuScreen::Profile),
+            _ => None,
+        }
+    }
+
+    /// Screens with text entry, Space and Backspace are typed there instead of navigating
+    fn takes_text(&self) -> bool {
+        *self == MenuScreen::Profile
+    }
+}
+
+impl MenuStack {
+    pub fn current(&self) -> MenuScreen {
+        *self.0.last().unwrap_or(&MenuScreen::Main)
+    }
+}
+
+fn menu_shown(state: Res<State<GameState>>) -> bool {
+    MenuScreen::root(&state.0).is_some()
+}
+
+/// Starts the screen stack of the entered state, a stack already rooted there is kept so
+/// returning from a theme reload lands back on the settings screen
+fn reset_menu(
+    state: Res<State<GameState>>,
+    mut stack: ResMut<MenuStack>,
+    mut focus: ResMut<MenuFocus>,
+) {
+    let root = MenuScreen::root(&state.0).unwrap_or(MenuScreen::Main);
+
+    if stack.0.first() != Some(&root) {
+        stack.0 = vec![root];
+        focus.0 = 0;
+    }
+
+    // Respawns the screen either way
+    stack.set_changed();
+}
+
+fn remember_game_over(
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut last_game_over: ResMut<LastGameOver>,
+) {
+    if let Some(event) = game_over_events.iter().last() {
+        last_game_over.0 = event.cause;
+    }
+}
+
+fn menu_buttons(
+    screen: MenuScreen,
+    effects: &CameraEffects,
+    palette: &Palette,
+    assists: &Assists,
+    themes: &Themes,
+    stats: &LifetimeStats,
+    locale: &Locale,
+) -> Vec<(String, MenuAction)> {
+    let on_off = |on: bool| locale.get(if on { "menu_on" } else { "menu_off" });
+    let button = |key: &str, action| (locale.get(key).to_string(), action);
+
+    match screen {
+        MenuScreen::Main => {
+            let mut buttons = vec![button("menu_play", MenuAction::Play)];
+
+            if stats.last_level > 1 {
+                buttons.push((
+                    locale.format("menu_continue", &[&stats.last_level]),
+                    MenuAction::Continue,
+                ));
+            }
+
+            buttons.extend([
+                button("menu_settings", MenuAction::Open(MenuScreen::Settings)),
+                button("menu_high_scores", MenuAction::Open(MenuScreen::HighScores)),
+                button("menu_player_stats", MenuAction::PlayerStats),
+                button("menu_level_editor", MenuAction::LevelEditor),
+                button("menu_quit", MenuAction::Quit),
+            ]);
+
+            buttons
+        }
+        MenuScreen::Settings => vec![
+            (
+                locale.format("menu_screen_effects", &[&on_off(effects.enabled)]),
+                MenuAction::ToggleScreenEffects,
+            ),
+            (
+                locale.format("menu_language", &[&locale.language().name()]),
+                MenuAction::CycleLanguage,
+            ),
+            (
+                locale.format("menu_palette", &[&locale.get(palette.name())]),
+                MenuAction::CyclePalette,
+            ),
+            (
+                locale.format("menu_theme", &[&themes.current().name]),
+                MenuAction::CycleTheme,
+            ),
+            button("menu_assists", MenuAction::Open(MenuScreen::Assists)),
+            button("menu_back", MenuAction::Back),
+        ],
+        MenuScreen::Assists => vec![
+            (
+                locale.format(
+                    "menu_game_speed",
+                    &[&locale.number(assists.game_speed * 100.)],
+                ),
+                MenuAction::CycleGameSpeed,
+            ),
+            (
+                locale.format(
+                    "menu_paddle_width",
+                    &[&locale.number(assists.paddle_width * 100.)],
+                ),
+                MenuAction::CyclePaddleWidth,
+            ),
+            (
+                locale.format("menu_safety_floor", &[&on_off(assists.safety_floor)]),
+                MenuAction::ToggleSafetyFloor,
+            ),
+            button("menu_back", MenuAction::Back),
+        ],
+        MenuScreen::HighScores => vec![button("menu_back", MenuAction::Back)],
+        MenuScreen::LevelComplete => vec![button("tally_continue", MenuAction::NextLevel)],
+        MenuScreen::GameOver => vec![button("game_over_main_menu", MenuAction::MainMenu)],
+        MenuScreen::Profile => vec![
+            button("profile_previous", MenuAction::PreviousProfile),
+            button("profile_next", MenuAction::NextProfile),
+            button("profile_new", MenuAction::NewProfile),
+            button("menu_back", MenuAction::Back),
+        ],
+    }
+}
+
+fn high_score_table(high_scores: &HighScores, locale: &Locale) -> String {
+    if high_scores.0.is_empty() {
+        return locale.get("high_scores_empty").to_string();
+    }
+
+    high_scores
+        .0
+        .iter()
+        .enumerate()
+        .map(|(rank, entry)| {
+            format!(
+                "{:>2}. {:<16} {:>9}  {}{}\n",
+                rank + 1,
+                entry.name,
+                locale.number(entry.score as f32),
+                locale.format("high_scores_level", &[&entry.level]),
+                if entry.assisted {
+                    format!(" {}", locale.get("high_scores_assisted"))
+                } else {
+                    String::new()
+                }
+            )
+        })
+        .collect()
+}
+
+/// Reusable menu button, a coloured box with a centered label
+pub fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: Handle<Font>,
+    label: &str,
+    index: usize,
+    action: MenuAction,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(BUTTON_WIDTH), Val::Px(BUTTON_HEIGHT)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            MenuButton { index, action },
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font,
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    stack: Res<MenuStack>,
+    mut focus: ResMut<MenuFocus>,
+    effects: Res<CameraEffects>,
+    palette: Res<Palette>,
+    assists: Res<Assists>,
+    themes: Res<Themes>,
+    stats: Res<LifetimeStats>,
+    high_scores: Res<HighScores>,
+    locale: Res<Locale>,
+    last_game_over: Res<LastGameOver>,
+    tally: TallySource,
+    profile: ProfileSource,
+    roots: Query<Entity, With<MenuRoot>>,
+) {
+    for entity in &roots {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let screen = stack.current();
+    let buttons = menu_buttons(
+        screen, &effects, &palette, &assists, &themes, &stats, &locale,
+    );
+    focus.0 = focus.0.min(buttons.len() - 1);
+
+    let font = assets.font.bold.clone();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    gap: Size::new(Val::Px(0.), Val::Px(10.)),
+                    ..default()
+                },
+                ..default()
+            },
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            let title = match screen {
+                MenuScreen::Main => "menu_title",
+                MenuScreen::Settings => "menu_settings",
+                MenuScreen::Assists => "menu_assists",
+                MenuScreen::HighScores => "menu_high_scores",
+                MenuScreen::LevelComplete => "level_complete",
+                MenuScreen::GameOver => "game_over",
+                MenuScreen::Profile => "menu_player_stats",
+            };
+
+            parent.spawn(
+                TextBundle::from_section(
+                    locale.get(title),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: if screen == MenuScreen::Main {
+                            150.0
+                        } else {
+                            80.0
+                        },
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(30.)),
+                    ..default()
+                }),
+            );
+
+            match screen {
+                MenuScreen::HighScores => {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            high_score_table(&high_scores, &locale),
+                            TextStyle {
+                                font: assets.font.mono.clone(),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(30.)),
+                            ..default()
+                        }),
+                    );
+                }
+                MenuScreen::GameOver => {
+                    let cause = match last_game_over.0 {
+                        GameOverCause::OutOfBalls => "game_over_out_of_balls",
+                        GameOverCause::BricksReachedPaddle => "game_over_bricks_reached_paddle",
+                    };
+
+                    parent.spawn(
+                        TextBundle::from_section(
+                            locale.get(cause),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(30.)),
+                            ..default()
+                        }),
+                    );
+                }
+                MenuScreen::LevelComplete => spawn_tally_lines(parent, &assets, &locale, &tally),
+                MenuScreen::Profile => spawn_profile_text(parent, &assets, &locale, &profile),
+                _ => {}
+            }
+
+            for (index, (label, action)) in buttons.iter().enumerate() {
+                spawn_button(parent, font.clone(), label, index, *action);
+            }
+        });
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn navigate_menu(
+    key: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    stack: Res<MenuStack>,
+    mut focus: ResMut<MenuFocus>,
+    mut action_events: EventWriter<MenuActionEvent>,
+    buttons: Query<&MenuButton>,
+    interactions: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+) {
+    let count = buttons.iter().count();
+
+    if count == 0 {
+        return;
+    }
+
+    let text_entry = stack.current().takes_text();
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if key.just_pressed(KeyCode::Up) || gamepad_pressed(GamepadButtonType::DPadUp) {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+
+    if key.just_pressed(KeyCode::Down) || gamepad_pressed(GamepadButtonType::DPadDown) {
+        focus.0 = (focus.0 + 1) % count;
+    }
+
+    if key.just_pressed(KeyCode::Return)
+        || (key.just_pressed(KeyCode::Space) && !text_entry)
+        || gamepad_pressed(GamepadButtonType::South)
+    {
+        if let Some(button) = buttons.iter().find(|button| button.index == focus.0) {
+            action_events.send(MenuActionEvent(button.action));
+        }
+    }
+
+    if key.just_pressed(KeyCode::Escape)
+        || (key.just_pressed(KeyCode::Back) && !text_entry)
+        || gamepad_pressed(GamepadButtonType::East)
+    {
+        action_events.send(MenuActionEvent(MenuAction::Back));
+    }
+
+    for (interaction, button) in &interactions {
+        match interaction {
+            Interaction::Hovered => focus.0 = button.index,
+            Interaction::Clicked => {
+                focus.0 = button.index;
+                action_events.send(MenuActionEvent(button.action));
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+fn highlight_focused_button(
+    focus: Res<MenuFocus>,
+    mut query: Query<(&MenuButton, &mut BackgroundColor)>,
+) {
+    for (button, mut color) in &mut query {
+        *color = if button.index == focus.0 {
+            BUTTON_FOCUS_COLOR.into()
+        } else {
+            BUTTON_COLOR.into()
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_menu_actions(
+    mut action_events: EventReader<MenuActionEvent>,
+    mut stack: ResMut<MenuStack>,
+    mut focus: ResMut<MenuFocus>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player_progress: ResMut<PlayerProgress>,
+    mut effects: ResMut<CameraEffects>,
+    mut locale: ResMut<Locale>,
+    mut palette: ResMut<Palette>,
+    mut assists: ResMut<Assists>,
+    mut themes: ResMut<Themes>,
+    mut exit_events: EventWriter<AppExit>,
+    stats: Res<LifetimeStats>,
+    playtest: Res<EditorPlaytest>,
+) {
+    // Playtests return to the editor instead of the main menu
+    let main_menu = if playtest.0 {
+        GameState::Editor
+    } else {
+        GameState::Start
+    };
+
+    for event in action_events.iter() {
+        match event.0 {
+            MenuAction::Play => {
+                *player_progress = PlayerProgress::default();
+                *next_state = NextState(Some(GameState::Playing));
+            }
+            MenuAction::Continue => {
+                *player_progress = PlayerProgress {
+                    level: stats.last_level,
+                    ..default()
+                };
+                *next_state = NextState(Some(GameState::Playing));
+            }
+            MenuAction::Open(screen) => {
+                stack.0.push(screen);
+                focus.0 = 0;
+            }
+            MenuAction::PlayerStats => *next_state = NextState(Some(GameState::Profile)),
+            MenuAction::LevelEditor => *next_state = NextState(Some(GameState::Editor)),
+            MenuAction::Quit => exit_events.send(AppExit),
+            MenuAction::ToggleScreenEffects => {
+                effects.enabled = !effects.enabled;
+                // Rebuild the screen so the label shows the new value
+                stack.set_changed();
+            }
+            MenuAction::CycleLanguage => {
+                locale.next();
+                locale.save();
+                stack.set_changed();
+            }
+            MenuAction::CyclePalette => {
+                *palette = palette.next();
+                palette.save();
+                stack.set_changed();
+            }
+            MenuAction::CycleTheme => {
+                // Switching reloads the assets behind the loading screen
+                themes.next();
+                themes.save();
+            }
+            MenuAction::CycleGameSpeed => {
+                assists.cycle_game_speed();
+                assists.save();
+                stack.set_changed();
+            }
+            MenuAction::CyclePaddleWidth => {
+                assists.cycle_paddle_width();
+                assists.save();
+                stack.set_changed();
+            }
+            MenuAction::ToggleSafetyFloor => {
+                assists.safety_floor = !assists.safety_floor;
+                assists.save();
+                stack.set_changed();
+            }
+            MenuAction::MainMenu => *next_state = NextState(Some(main_menu.clone())),
+            MenuAction::Back => {
+                if stack.0.len() > 1 {
+                    stack.0.pop();
+                    focus.0 = 0;
+                } else if matches!(stack.current(), MenuScreen::GameOver | MenuScreen::Profile) {
+                    *next_state = NextState(Some(main_menu.clone()));
+                }
+            }
+            // Handled by the plugins of their states
+            MenuAction::NextLevel
+            | MenuAction::PreviousProfile
+            | MenuAction::NextProfile
+            | MenuAction::NewProfile => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continue_only_offered_past_level_one() {
+        let effects = CameraEffects {
+            enabled: true,
+            stack: vec![],
+        };
+        let themes = Themes {
+            installed: vec![ThemeManifest::default_theme()],
+            current: 0,
+        };
+        let locale = Locale::discover();
+        let mut stats = LifetimeStats::default();
+
+        let has_continue = |stats: &LifetimeStats| {
+            menu_buttons(
+                MenuScreen::Main,
+                &effects,
+                &Palette::Standard,
+                &Assists::default(),
+                &themes,
+                stats,
+                &locale,
+            )
+            .iter()
+            .any(|(_, action)| *action == MenuAction::Continue)
+        };
+
+        assert!(!has_continue(&stats));
+        stats.last_level = 3;
+        assert!(has_continue(&stats));
+    }
+
+    #[test]
+    fn every_menu_state_has_a_screen_with_buttons() {
+        let effects = CameraEffects {
+            enabled: true,
+            stack: vec![],
+        };
+        let themes = Themes {
+            installed: vec![ThemeManifest::default_theme()],
+            current: 0,
+        };
+        let locale = Locale::discover();
+
+        for state in MENU_STATES {
+            let screen = MenuScreen::root(&state).unwrap();
+            let buttons = menu_buttons(
+                screen,
+                &effects,
+                &Palette::Standard,
+                &Assists::default(),
+                &themes,
+                &LifetimeStats::default(),
+                &locale,
+            );
+
+            assert!(!buttons.is_empty(), "{:?} has no buttons", screen);
+        }
+
+        assert_eq!(MenuScreen::root(&GameState::Playing), None);
+    }
+}
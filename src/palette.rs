@@ -0,0 +1,230 @@
+use crate::*;
+
+const PALETTE_SAVE: &str = "palette";
+
+pub struct PalettePlugin;
+
+/// Brick colours, the accessible ones tint the greyscale brick frames and add a glyph per kind
+#[derive(Resource, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Palette {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 5] = [
+        Palette::Standard,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Palette::Standard => "standard",
+            Palette::Deuteranopia => "deuteranopia",
+            Palette::Protanopia => "protanopia",
+            Palette::Tritanopia => "tritanopia",
+            Palette::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Palette::ALL
+            .into_iter()
+            .find(|palette| palette.code() == code.trim())
+    }
+
+    /// Locale key of the palette name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Standard => "palette_standard",
+            Palette::Deuteranopia => "palette_deuteranopia",
+            Palette::Protanopia => "palette_protanopia",
+            Palette::Tritanopia => "palette_tritanopia",
+            Palette::HighContrast => "palette_high_contrast",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Palette::ALL.iter().position(|p| p == self).unwrap_or(0);
+
+        Palette::ALL[(index + 1) % Palette::ALL.len()]
+    }
+
+    pub fn brick_color(&self, color: BrickColor) -> Color {
+        let index = BrickColor::ALL
+            .iter()
+            .position(|c| *c == color)
+            .unwrap_or(0);
+
+        // Colour sets that stay apart under each kind of colour blindness
+        let [r, g, b] = match self {
+            Palette::Standard => return color.color(),
+            Palette::Deuteranopia => [
+                [0.0, 0.45, 0.7],
+                [0.9, 0.6, 0.0],
+                [0.95, 0.9, 0.25],
+                [0.35, 0.7, 0.9],
+                [0.8, 0.6, 0.7],
+                [0.85, 0.85, 0.85],
+            ][index],
+            Palette::Protanopia => [
+                [0.0, 0.45, 0.7],
+                [0.95, 0.9, 0.25],
+                [0.35, 0.7, 0.9],
+                [0.9, 0.6, 0.0],
+                [0.55, 0.55, 0.55],
+                [1.0, 1.0, 1.0],
+            ][index],
+            Palette::Tritanopia => [
+                [0.86, 0.15, 0.15],
+                [0.0, 0.6, 0.6],
+                [1.0, 0.55, 0.65],
+                [0.5, 0.5, 0.5],
+                [0.95, 0.95, 0.95],
+                [0.45, 0.1, 0.45],
+            ][index],
+            Palette::HighContrast => [
+                [1.0, 1.0, 1.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 1.0],
+                [1.0, 0.0, 1.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 0.5, 0.0],
+            ][index],
+        };
+
+        Color::rgb(r, g, b)
+    }
+
+    pub fn background_tint(&self) -> Color {
+        match self {
+            Palette::HighContrast => Color::rgb(0.08, 0.08, 0.08),
+            _ => Color::rgba(0.2, 0.5, 0.7, 0.9),
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = write_save(PALETTE_SAVE, self.code()) {
+            warn!("Could not save palette: {}", error);
+        }
+    }
+}
+
+/// Dark glyphs on light bricks and light ones on dark bricks
+pub fn glyph_color(brick_color: Color) -> Color {
+    let [r, g, b, _] = brick_color.as_rgba_f32();
+
+    if 0.3 * r + 0.6 * g + 0.1 * b > 0.45 {
+        Color::rgba(0., 0., 0., 0.6)
+    } else {
+        Color::rgba(1., 1., 1., 0.8)
+    }
+}
+
+/// Bars making up the glyph of a brick kind, as offset, size and rotation
+pub fn glyph_shapes(brick_type: BrickType, color: BrickColor) -> Vec<(Vec2, Vec2, f32)> {
+    use std::f32::consts::FRAC_PI_4;
+
+    let horizontal = (Vec2::ZERO, Vec2::new(20., 4.), 0.);
+    let vertical = (Vec2::ZERO, Vec2::new(4., 18.), 0.);
+    let dot = |x: f32| (Vec2::new(x, 0.), Vec2::splat(6.), 0.);
+
+    if brick_type == BrickType::Bomb {
+        return vec![
+            (Vec2::ZERO, Vec2::new(22., 4.), FRAC_PI_4),
+            (Vec2::ZERO, Vec2::new(22., 4.), -FRAC_PI_4),
+        ];
+    }
+
+    match color {
+        BrickColor::Red => vec![horizontal],
+        BrickColor::Orange => vec![vertical],
+        BrickColor::Yellow => vec![horizontal, vertical],
+        BrickColor::Green => vec![dot(-8.), dot(8.)],
+        BrickColor::LightGreen => vec![(Vec2::ZERO, Vec2::splat(10.), FRAC_PI_4)],
+        BrickColor::Blue => vec![dot(-12.), dot(0.), dot(12.)],
+    }
+}
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            read_save(PALETTE_SAVE)
+                .ok()
+                .and_then(|code| Palette::from_code(&code))
+                .unwrap_or_default(),
+        )
+        .add_system(apply_palette)
+        .add_system(apply_background_palette.run_if(resource_changed::<Palette>()));
+    }
+}
+
+fn apply_palette(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    sheet: Res<SpriteSheet>,
+    mut query: Query<(Entity, &Brick, &mut TextureAtlasSprite), Added<Brick>>,
+) {
+    if *palette == Palette::Standard {
+        return;
+    }
+
+    for (entity, brick, mut sprite) in &mut query {
+        let color = palette.brick_color(brick.color);
+
+        // Greyscale brick tinted in the palette colour
+        sprite.index = sheet.grey_brick(brick.brick_type).first();
+        sprite.color = color;
+
+        commands.entity(entity).with_children(|parent| {
+            for (offset, size, rotation) in glyph_shapes(brick.brick_type, brick.color) {
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: glyph_color(color),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(offset.extend(0.2))
+                        .with_rotation(Quat::from_rotation_z(rotation)),
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+fn apply_background_palette(
+    palette: Res<Palette>,
+    mut query: Query<&mut Sprite, With<Background>>,
+) {
+    for mut sprite in &mut query {
+        sprite.color = palette.background_tint();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn brick_kinds_have_distinct_tints_and_glyphs() {
+        for palette in Palette::ALL {
+            for (i, a) in BrickColor::ALL.iter().enumerate() {
+                for b in &BrickColor::ALL[i + 1..] {
+                    assert_ne!(palette.brick_color(*a), palette.brick_color(*b));
+                    assert_ne!(
+                        glyph_shapes(BrickType::Regular, *a),
+                        glyph_shapes(BrickType::Regular, *b)
+                    );
+                }
+            }
+        }
+    }
+}
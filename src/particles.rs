@@ -0,0 +1,231 @@
+use std::f32::consts::TAU;
+
+use crate::*;
+
+const PARTICLE_Z_VALUE: f32 = 50.;
+const DEBRIS_COUNT: usize = 14;
+const EXPLOSION_COUNT: usize = 40;
+const SPARK_COUNT: usize = 6;
+const TRAIL_RATE: f32 = 60.;
+/// Half angle of the cone debris and sparks fly out in
+const SPRAY_SPREAD: f32 = 1.;
+/// Ball speed at which sprays reach their base speed
+const SPRAY_REFERENCE_SPEED: f32 = 400.;
+
+pub struct ParticlePlugin;
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub gravity: f32,
+    pub age: f32,
+    pub lifetime: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+}
+
+/// Continuously emits particles from the entity it is attached to
+#[derive(Component)]
+pub struct ParticleSpawner {
+    /// Particles per second
+    pub rate: f32,
+    pub accumulator: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    pub size: f32,
+    pub lifetime: f32,
+}
+
+impl ParticleSpawner {
+    pub fn flame_trail() -> Self {
+        ParticleSpawner {
+            rate: TRAIL_RATE,
+            accumulator: 0.,
+            start_color: Color::rgba(1., 0.8, 0.2, 0.9),
+            end_color: Color::rgba(0.8, 0.1, 0., 0.),
+            size: BALL_SIZE * 0.6,
+            lifetime: 0.4,
+        }
+    }
+}
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((update_particles, emit_particles))
+            .add_systems(
+                (spawn_brick_debris, spawn_collision_sparks).in_set(OnUpdate(GameState::Playing)),
+            )
+            .add_system(despawn::<Particle>.in_schedule(OnExit(GameState::Playing)));
+    }
+}
+
+fn spawn_particle(commands: &mut Commands, position: Vec3, particle: Particle) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: particle.start_color,
+                custom_size: Some(Vec2::splat(particle.start_size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, PARTICLE_Z_VALUE),
+            ..default()
+        },
+        particle,
+    ));
+}
+
+fn random_direction() -> Vec2 {
+    Vec2::from_angle(rand::random::<f32>() * TAU)
+}
+
+/// Random direction within the spray cone around `direction`, any direction if it is zero
+fn spray_direction(direction: Vec2) -> Vec2 {
+    if direction == Vec2::ZERO {
+        return random_direction();
+    }
+
+    let angle = (rand::random::<f32>() * 2. - 1.) * SPRAY_SPREAD;
+
+    Vec2::from_angle(angle).rotate(direction.normalize())
+}
+
+/// Faster balls throw particles further
+fn spray_speed(speed: f32) -> f32 {
+    (speed / SPRAY_REFERENCE_SPEED).clamp(0.5, 2.)
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        particle.age += delta;
+
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        particle.velocity.y -= particle.gravity * delta;
+        transform.translation += particle.velocity.extend(0.) * delta;
+
+        let t = particle.age / particle.lifetime;
+        let [r1, g1, b1, a1] = particle.start_color.as_rgba_f32();
+        let [r2, g2, b2, a2] = particle.end_color.as_rgba_f32();
+
+        sprite.color = Color::rgba(
+            r1 + (r2 - r1) * t,
+            g1 + (g2 - g1) * t,
+            b1 + (b2 - b1) * t,
+            a1 + (a2 - a1) * t,
+        );
+        sprite.custom_size = Some(Vec2::splat(
+            particle.start_size + (particle.end_size - particle.start_size) * t,
+        ));
+    }
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(&mut ParticleSpawner, &Transform)>,
+) {
+    for (mut spawner, transform) in query.iter_mut() {
+        spawner.accumulator += spawner.rate * time.delta_seconds();
+
+        while spawner.accumulator >= 1. {
+            spawner.accumulator -= 1.;
+
+            spawn_particle(
+                &mut commands,
+                transform.translation + (random_direction() * BALL_SIZE / 4.).extend(0.),
+                Particle {
+                    velocity: random_direction() * 20.,
+                    gravity: 0.,
+                    age: 0.,
+                    lifetime: spawner.lifetime,
+                    start_color: spawner.start_color,
+                    end_color: spawner.end_color,
+                    start_size: spawner.size,
+                    end_size: 0.,
+                },
+            );
+        }
+    }
+}
+
+fn spawn_brick_debris(
+    mut commands: Commands,
+    mut destruction_events: EventReader<BrickDesctructionEvent>,
+    palette: Res<Palette>,
+) {
+    for event in destruction_events.iter() {
+        // Debris flies on in the direction the ball was going, explosions go everywhere
+        let (count, color, speed, direction) = match event.brick_type {
+            BrickType::Bomb => (EXPLOSION_COUNT, Color::rgb(1., 0.6, 0.1), 400., Vec2::ZERO),
+            _ => (
+                DEBRIS_COUNT,
+                palette.brick_color(event.color),
+                200. * spray_speed(event.speed),
+                -event.normal,
+            ),
+        };
+
+        for _ in 0..count {
+            let offset = Vec2::new(
+                (rand::random::<f32>() - 0.5) * BRICK_WIDTH,
+                (rand::random::<f32>() - 0.5) * BRICK_HEIGHT,
+            );
+
+            spawn_particle(
+                &mut commands,
+                event.position + offset.extend(0.),
+                Particle {
+                    velocity: spray_direction(direction) * speed * (0.3 + rand::random::<f32>()),
+                    gravity: 600.,
+                    age: 0.,
+                    lifetime: 0.5 + rand::random::<f32>() * 0.4,
+                    start_color: color,
+                    end_color: color.with_a(0.),
+                    start_size: 4. + rand::random::<f32>() * 6.,
+                    end_size: 2.,
+                },
+            );
+        }
+    }
+}
+
+fn spawn_collision_sparks(
+    mut commands: Commands,
+    mut collision_events: EventReader<BallCollisionEvent>,
+) {
+    for event in collision_events.iter() {
+        let speed = 150. * spray_speed(event.speed);
+        let (start_color, end_color) = match event.ball_type {
+            BallType::Regular => (Color::rgb(1., 1., 0.8), Color::rgba(1., 0.7, 0.2, 0.)),
+            BallType::FireBall => (Color::rgb(1., 0.6, 0.1), Color::rgba(0.8, 0.1, 0., 0.)),
+        };
+
+        for _ in 0..SPARK_COUNT {
+            spawn_particle(
+                &mut commands,
+                event.position,
+                Particle {
+                    velocity: spray_direction(event.normal) * speed * (1. + rand::random::<f32>()),
+                    gravity: 0.,
+                    age: 0.,
+                    lifetime: 0.2 + rand::random::<f32>() * 0.15,
+                    start_color,
+                    end_color,
+                    start_size: 3.,
+                    end_size: 1.,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use bevy::ecs::system::SystemParam;
+
+use crate::*;
+
+const PROFILES_SAVE: &str = "profiles";
+const MAX_PROFILE_NAME_LENGTH: usize = 16;
+
+pub struct ProfilePlugin;
+
+/// Local players sharing this machine, each with their own records
+#[derive(Resource)]
+pub struct Profiles {
+    pub names: Vec<String>,
+    pub current: usize,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Profiles {
+            names: vec!["Player 1".to_string()],
+            current: 0,
+        }
+    }
+}
+
+impl Profiles {
+    pub fn parse(source: &str) -> Self {
+        let mut profiles = Profiles {
+            names: vec![],
+            current: 0,
+        };
+
+        for line in source.lines() {
+            if let Some(current) = line.strip_prefix("current:") {
+                profiles.current = current.trim().parse().unwrap_or(0);
+            } else if !line.trim().is_empty() {
+                profiles.names.push(line.trim().to_string());
+            }
+        }
+
+        if profiles.names.is_empty() {
+            return Profiles::default();
+        }
+
+        profiles.current = profiles.current.min(profiles.names.len() - 1);
+        profiles
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut source = format!("current: {}\n", self.current);
+
+        for name in &self.names {
+            source.push_str(name);
+            source.push('\n');
+        }
+
+        source
+    }
+
+    /// Gives blank names back a default one, the save file skips blank lines and would
+    /// shift every later profile onto the records of the one before it
+    pub fn restore_blank_names(&mut self, locale: &Locale) {
+        for (index, name) in self.names.iter_mut().enumerate() {
+            if name.trim().is_empty() {
+                *name = locale.format("profile_new_name", &[&(index + 1)]);
+            }
+        }
+    }
+
+    pub fn current_name(&self) -> &str {
+        &self.names[self.current]
+    }
+
+    /// Save file of the current profile, profiles are numbered so renaming keeps their records
+    pub fn save_name(&self, kind: &str) -> String {
+        format!("profile_{}.{}", self.current, kind)
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = write_save(PROFILES_SAVE, &self.serialize()) {
+            warn!("Could not save profiles: {}", error);
+        }
+    }
+}
+
+/// Records of the current profile across all sessions
+#[derive(Resource, Default, Clone, PartialEq, Debug)]
+pub struct LifetimeStats {
+    pub total_bricks: u32,
+    pub games_played: u32,
+    pub highest_level: u16,
+    /// Longest single game in seconds
+    pub longest_session: f32,
+    pub fireballs: u32,
+    pub bombs: u32,
+    /// Level the last game ended on, where Continue picks up
+    pub last_level: u16,
+}
+
+impl LifetimeStats {
+    pub fn parse(source: &str) -> Self {
+        let mut stats = LifetimeStats::default();
+
+        for line in source.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim() {
+                "total_bricks" => stats.total_bricks = value.parse().unwrap_or(0),
+                "games_played" => stats.games_played = value.parse().unwrap_or(0),
+                "highest_level" => stats.highest_level = value.parse().unwrap_or(0),
+                "longest_session" => stats.longest_session = value.parse().unwrap_or(0.),
+                "fireballs" => stats.fireballs = value.parse().unwrap_or(0),
+                "bombs" => stats.bombs = value.parse().unwrap_or(0),
+                "last_level" => stats.last_level = value.parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+
+        stats
+    }
+
+    pub fn serialize(&self) -> String {
+        format!(
+            "total_bricks: {}\ngames_played: {}\nhighest_level: {}\nlongest_session: {}\nfireballs: {}\nbombs: {}\nlast_level: {}\n",
+            self.total_bricks,
+            self.games_played,
+            self.highest_level,
+            self.longest_session,
+            self.fireballs,
+            self.bombs,
+            self.last_level
+        )
+    }
+
+    /// Most triggered power-up brick, if any was triggered at all
+    pub fn favourite_powerup(&self) -> Option<BrickType> {
+        if self.fireballs == 0 && self.bombs == 0 {
+            None
+        } else if self.fireballs >= self.bombs {
+            Some(BrickType::Fireball)
+        } else {
+            Some(BrickType::Bomb)
+        }
+    }
+}
+
+/// Time spent playing in the current game, zero until it starts
+#[derive(Resource, Default)]
+pub struct SessionClock(pub f32);
+
+#[derive(Component)]
+pub struct ProfileText;
+
+/// What the profile menu screen shows about the current profile
+#[derive(SystemParam)]
+pub struct ProfileSource<'w> {
+    profiles: Res<'w, Profiles>,
+    stats: Res<'w, LifetimeStats>,
+    unlocked: Res<'w, UnlockedAchievements>,
+}
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let profiles = read_save(PROFILES_SAVE)
+            .map(|source| Profiles::parse(&source))
+            .unwrap_or_default();
+
+        app.insert_resource(load_lifetime_stats(&profiles))
+            .insert_resource(load_unlocked_achievements(&profiles))
+            .insert_resource(profiles)
+            .insert_resource(SessionClock::default());
+
+        // Playing state
+        app.add_systems(
+            (
+                start_game_session.before(track_lifetime_stats),
+                track_lifetime_stats,
+            )
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(save_lifetime_stats.in_schedule(OnEnter(GameState::LevelCompleted)))
+        .add_systems(
+            (end_game_session, save_lifetime_stats, reset_session_clock)
+                .chain()
+                .in_schedule(OnEnter(GameState::GameOver)),
+        );
+
+        // Profile state, the screen itself is a menu screen
+        app.add_system(clear_typed_characters.in_schedule(OnEnter(GameState::Profile)))
+            .add_systems(
+                (
+                    profile_hotkeys,
+                    select_profile.after(profile_hotkeys),
+                    rename_profile,
+                    update_profile_text
+                        .after(select_profile)
+                        .after(rename_profile),
+                )
+                    .in_set(OnUpdate(GameState::Profile)),
+            )
+            .add_system(close_profile_screen.in_schedule(OnExit(GameState::Profile)));
+    }
+}
+
+fn load_lifetime_stats(profiles: &Profiles) -> LifetimeStats {
+    read_save(&profiles.save_name("stats"))
+        .map(|source| LifetimeStats::parse(&source))
+        .unwrap_or_default()
+}
+
+fn load_unlocked_achievements(profiles: &Profiles) -> UnlockedAchievements {
+    read_save(&profiles.save_name("achievements"))
+        .map(|source| UnlockedAchievements::parse(&source))
+        .unwrap_or_default()
+}
+
+fn close_profile_screen(locale: Res<Locale>, mut profiles: ResMut<Profiles>) {
+    profiles.restore_blank_names(&locale);
+    profiles.save();
+}
+
+fn start_game_session(
+    mut stats: ResMut<LifetimeStats>,
+    mut started_events: EventReader<LevelStartedEvent>,
+    clock: Res<SessionClock>,
+    playtest: Res<EditorPlaytest>,
+) {
+    for event in started_events.iter() {
+        if playtest.0 {
+            continue;
+        }
+
+        if clock.0 == 0. {
+            stats.games_played += 1;
+        }
+
+        stats.highest_level = stats.highest_level.max(event.level);
+    }
+}
+
+fn track_lifetime_stats(
+    mut stats: ResMut<LifetimeStats>,
+    mut clock: ResMut<SessionClock>,
+    mut destruction_events: EventReader<BrickDesctructionEvent>,
+    playtest: Res<EditorPlaytest>,
+    time: Res<Time>,
+) {
+    if playtest.0 {
+        return;
+    }
+
+    clock.0 += time.delta_seconds();
+
+    for event in destruction_events.iter() {
+        stats.total_bricks += 1;
+
+        match event.brick_type {
+            BrickType::Fireball => stats.fireballs += 1,
+            BrickType::Bomb => stats.bombs += 1,
+            BrickType::Regular => {}
+        }
+    }
+}
+
+fn save_lifetime_stats(
+    mut stats: ResMut<LifetimeStats>,
+    clock: Res<SessionClock>,
+    profiles: Res<Profiles>,
+    playtest: Res<EditorPlaytest>,
+) {
+    if playtest.0 {
+        return;
+    }
+
+    stats.longest_session = stats.longest_session.max(clock.0);
+
+    if let Err(error) = write_save(&profiles.save_name("stats"), &stats.serialize()) {
+        warn!("Could not save stats: {}", error);
+    }
+}
+
+fn reset_session_clock(mut clock: ResMut<SessionClock>) {
+    clock.0 = 0.;
+}
+
+/// Drops the key press that opened the screen so it does not end up in the name
+fn clear_typed_characters(mut characters: ResMut<Events<ReceivedCharacter>>) {
+    characters.clear();
+}
+
+fn end_game_session(
+    mut stats: ResMut<LifetimeStats>,
+    player_progress: Res<PlayerProgress>,
+    playtest: Res<EditorPlaytest>,
+) {
+    if !playtest.0 {
+        stats.last_level = player_progress.level;
+    }
+}
+
+/// Shortcuts for the profile buttons
+fn profile_hotkeys(key: Res<Input<KeyCode>>, mut action_events: EventWriter<MenuActionEvent>) {
+    if key.just_pressed(KeyCode::Left) {
+        action_events.send(MenuActionEvent(MenuAction::PreviousProfile));
+    } else if key.just_pressed(KeyCode::Right) {
+        action_events.send(MenuActionEvent(MenuAction::NextProfile));
+    } else if key.just_pressed(KeyCode::Tab) {
+        action_events.send(MenuActionEvent(MenuAction::NewProfile));
+    }
+}
+
+fn select_profile(
+    mut action_events: EventReader<MenuActionEvent>,
+    locale: Res<Locale>,
+    mut profiles: ResMut<Profiles>,
+    mut stats: ResMut<LifetimeStats>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+) {
+    for event in action_events.iter() {
+        let count = profiles.names.len();
+        let current = profiles.current;
+
+        match event.0 {
+            MenuAction::PreviousProfile => profiles.current = (current + count - 1) % count,
+            MenuAction::NextProfile => profiles.current = (current + 1) % count,
+            MenuAction::NewProfile => {
+                profiles
+                    .names
+                    .push(locale.format("profile_new_name", &[&(count + 1)]));
+                profiles.current = count;
+            }
+            _ => continue,
+        }
+
+        profiles.restore_blank_names(&locale);
+        *stats = load_lifetime_stats(&profiles);
+        *unlocked = load_unlocked_achievements(&profiles);
+    }
+}
+
+fn rename_profile(
+    key: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut profiles: ResMut<Profiles>,
+) {
+    let typed: Vec<char> = characters
+        .iter()
+        .map(|event| event.char)
+        .filter(|c| c.is_alphanumeric() || *c == ' ')
+        .collect();
+    let erased = key.just_pressed(KeyCode::Back);
+
+    // Only touch the profiles when the name changes, the profile text rebuilds on every change
+    if typed.is_empty() && !erased {
+        return;
+    }
+
+    let current = profiles.current;
+    let name = &mut profiles.names[current];
+
+    if erased {
+        name.pop();
+    }
+
+    for c in typed {
+        if name.chars().count() < MAX_PROFILE_NAME_LENGTH {
+            name.push(c);
+        }
+    }
+}
+
+fn profile_screen_text(
+    profiles: &Profiles,
+    stats: &LifetimeStats,
+    unlocked: &UnlockedAchievements,
+    locale: &Locale,
+) -> String {
+    let favourite_powerup = match stats.favourite_powerup() {
+        Some(BrickType::Fireball) => locale.get("powerup_fireball"),
+        Some(BrickType::Bomb) => locale.get("powerup_bomb"),
+        _ => "-",
+    };
+
+    [
+        format!("< {} >\n", profiles.current_name()),
+        locale.format(
+            "profile_bricks",
+            &[&locale.number(stats.total_bricks as f32)],
+        ),
+        locale.format("profile_games", &[&stats.games_played]),
+        locale.format("profile_highest_level", &[&stats.highest_level]),
+        locale.format(
+            "profile_longest_game",
+            &[&locale.duration(stats.longest_session)],
+        ),
+        locale.format("profile_favourite_powerup", &[&favourite_powerup]),
+        locale.format(
+            "profile_achievements",
+            &[&unlocked.0.len(), &ACHIEVEMENTS.len()],
+        ),
+    ]
+    .join("\n")
+}
+
+pub fn spawn_profile_text(
+    parent: &mut ChildBuilder,
+    assets: &GameAssets,
+    locale: &Locale,
+    profile: &ProfileSource,
+) {
+    parent.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                profile_screen_text(&profile.profiles, &profile.stats, &profile.unlocked, locale),
+                TextStyle {
+                    font: assets.font.bold.clone(),
+                    font_size: 34.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::new(
+                format!("\n\n{}", locale.get("profile_help")),
+                TextStyle {
+                    font: assets.font.bold.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+        ])
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style {
+            margin: UiRect::bottom(Val::Px(30.)),
+            ..default()
+        }),
+        ProfileText,
+    ));
+}
+
+fn update_profile_text(
+    mut query: Query<&mut Text, With<ProfileText>>,
+    profiles: Res<Profiles>,
+    stats: Res<LifetimeStats>,
+    unlocked: Res<UnlockedAchievements>,
+    locale: Res<Locale>,
+) {
+    if !profiles.is_changed() && !stats.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = profile_screen_text(&profiles, &stats, &unlocked, &locale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_round_trip() {
+        let profiles = Profiles {
+            names: vec!["Ada".to_string(), "Linus".to_string()],
+            current: 1,
+        };
+        let parsed = Profiles::parse(&profiles.serialize());
+
+        assert_eq!(parsed.names, profiles.names);
+        assert_eq!(parsed.current, 1);
+        assert_eq!(Profiles::parse("").names.len(), 1);
+    }
+
+    #[test]
+    fn blank_names_keep_profile_indices() {
+        let mut profiles = Profiles {
+            names: vec!["Ada".to_string(), "  ".to_string(), "Linus".to_string()],
+            current: 2,
+        };
+        profiles.restore_blank_names(&Locale::discover());
+
+        let parsed = Profiles::parse(&profiles.serialize());
+
+        assert_eq!(parsed.names, ["Ada", "Player 2", "Linus"]);
+        assert_eq!(parsed.current_name(), "Linus");
+    }
+
+    #[test]
+    fn lifetime_stats_round_trip() {
+        let stats = LifetimeStats {
+            total_bricks: 1234,
+            games_played: 7,
+            highest_level: 12,
+            longest_session: 345.5,
+            fireballs: 3,
+            bombs: 9,
+            last_level: 4,
+        };
+
+        assert_eq!(LifetimeStats::parse(&stats.serialize()), stats);
+        assert_eq!(stats.favourite_powerup(), Some(BrickType::Bomb));
+        assert_eq!(LifetimeStats::default().favourite_powerup(), None);
+    }
+}
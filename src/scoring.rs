@@ -0,0 +1,358 @@
+use crate::*;
+
+pub const BASE_BRICK_SCORE: f32 = 10.;
+/// Each brick in a chain is worth this much more than the previous one
+pub const CHAIN_GROWTH: f32 = 1.25;
+pub const MAX_CHAIN_MULTIPLIER: f32 = 20.;
+/// Seconds without a hit after which a chain breaks
+pub const CHAIN_TIMEOUT: f32 = 3.;
+/// Extra multiplier per bomb explosion that led to a brick
+pub const BOMB_CHAIN_MULTIPLIER: f32 = 0.5;
+pub const ROW_CLEAR_BONUS: f32 = 100.;
+pub const COLUMN_CLEAR_BONUS: f32 = 150.;
+pub const BALL_LOST_PENALTY: f32 = 250.;
+/// Levels cleared faster than this many seconds earn a time bonus
+pub const TIME_BONUS_PAR: f32 = 120.;
+pub const TIME_BONUS_PER_SECOND: f32 = 10.;
+
+pub struct ScoringPlugin;
+
+/// Consecutive bricks destroyed without the ball touching the paddle
+#[derive(Resource, Default)]
+pub struct ComboChain {
+    pub count: u32,
+    /// Longest chain in the current level
+    pub best: u32,
+}
+
+impl ComboChain {
+    /// Extends the chain and returns its new length
+    pub fn hit(&mut self) -> u32 {
+        self.count += 1;
+        self.best = self.best.max(self.count);
+        self.count
+    }
+
+    pub fn break_chain(&mut self) {
+        self.count = 0;
+    }
+}
+
+/// Time left before the current chain breaks
+#[derive(Resource)]
+pub struct ScoreIncrementTimer(pub Timer);
+
+/// Time spent in the current level
+#[derive(Resource, Default)]
+pub struct LevelClock(pub f32);
+
+/// Bricks left per row and column, to detect cleared lines
+#[derive(Resource)]
+pub struct RemainingBricks {
+    pub rows: [u32; LEVEL_ROWS],
+    pub columns: [u32; BRICK_COLUMNS],
+    /// Lines that already paid their bonus, regrown bricks cannot earn it again
+    rows_paid: [bool; LEVEL_ROWS],
+    columns_paid: [bool; BRICK_COLUMNS],
+}
+
+impl RemainingBricks {
+    pub fn from_level(level: &Level) -> Self {
+        let mut remaining = RemainingBricks {
+            rows: [0; LEVEL_ROWS],
+            columns: [0; BRICK_COLUMNS],
+            rows_paid: [false; LEVEL_ROWS],
+            columns_paid: [false; BRICK_COLUMNS],
+        };
+
+        for (y, row) in level.bricks.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if cell.is_some() {
+                    remaining.add((x, y));
+                }
+            }
+        }
+
+        remaining
+    }
+
+    pub fn add(&mut self, (x, y): (usize, usize)) {
+        self.rows[y] += 1;
+        self.columns[x] += 1;
+    }
+
+    /// Removes a brick and returns whether that cleared its row and column for the first time
+    pub fn remove(&mut self, (x, y): (usize, usize)) -> (bool, bool) {
+        self.rows[y] = self.rows[y].saturating_sub(1);
+        self.columns[x] = self.columns[x].saturating_sub(1);
+
+        let row_cleared = self.rows[y] == 0 && !self.rows_paid[y];
+        let column_cleared = self.columns[x] == 0 && !self.columns_paid[x];
+        self.rows_paid[y] |= row_cleared;
+        self.columns_paid[x] |= column_cleared;
+
+        (row_cleared, column_cleared)
+    }
+}
+
+/// Points for a brick hit as the `chain`th brick of a chain
+pub fn brick_score(chain: u32, chain_depth: u8) -> f32 {
+    let chain_multiplier = CHAIN_GROWTH
+        .powi(chain.saturating_sub(1) as i32)
+        .min(MAX_CHAIN_MULTIPLIER);
+    let bomb_multiplier = 1. + chain_depth as f32 * BOMB_CHAIN_MULTIPLIER;
+
+    BASE_BRICK_SCORE * chain_multiplier * bomb_multiplier
+}
+
+pub fn line_clear_bonus(row_cleared: bool, column_cleared: bool) -> f32 {
+    let mut bonus = 0.;
+
+    if row_cleared {
+        bonus += ROW_CLEAR_BONUS;
+    }
+
+    if column_cleared {
+        bonus += COLUMN_CLEAR_BONUS;
+    }
+
+    bonus
+}
+
+/// Points lost for losing a ball, never more than the current score
+pub fn ball_lost_penalty(score: f32) -> f32 {
+    BALL_LOST_PENALTY.min(score.max(0.))
+}
+
+pub fn level_clear_bonus(seconds: f32) -> f32 {
+    ((TIME_BONUS_PAR - seconds).max(0.) * TIME_BONUS_PER_SECOND).round()
+}
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ComboChain::default())
+            .insert_resource(ScoreIncrementTimer(Timer::from_seconds(
+                CHAIN_TIMEOUT,
+                TimerMode::Once,
+            )))
+            .insert_resource(LevelClock::default())
+            .insert_resource(RemainingBricks::from_level(&Level::default()));
+
+        app.add_systems(
+            (reset_bonus_score, reset_scoring)
+                .after(load_level)
+                .in_schedule(OnEnter(GameState::Playing)),
+        )
+        .add_systems(
+            (
+                tick_level_clock,
+                break_chain.before(update_score),
+                update_score,
+                penalize_ball_loss,
+            )
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(award_level_clear_bonus.in_schedule(OnEnter(GameState::LevelCompleted)));
+    }
+}
+
+fn reset_bonus_score(mut progress: ResMut<PlayerProgress>) {
+    progress.bonus_score = 0.;
+}
+
+fn reset_scoring(
+    mut combo: ResMut<ComboChain>,
+    mut timer: ResMut<ScoreIncrementTimer>,
+    mut clock: ResMut<LevelClock>,
+    mut remaining: ResMut<RemainingBricks>,
+    level: Res<CurrentLevel>,
+) {
+    *combo = ComboChain::default();
+    *clock = LevelClock::default();
+    *remaining = RemainingBricks::from_level(&level.0);
+
+    // Start finished so the first brick starts a fresh chain
+    let duration = timer.0.duration();
+    timer.0.set_elapsed(duration);
+}
+
+fn tick_level_clock(mut clock: ResMut<LevelClock>, time: Res<Time>) {
+    clock.0 += time.delta_seconds();
+}
+
+fn break_chain(
+    mut collision_events: EventReader<BallCollisionEvent>,
+    mut combo: ResMut<ComboChain>,
+    mut timer: ResMut<ScoreIncrementTimer>,
+    time: Res<Time>,
+) {
+    timer.0.tick(time.delta());
+
+    let paddle_hit = collision_events
+        .iter()
+        .any(|event| event.collision_type == BallCollisionType::Paddle);
+
+    if paddle_hit || timer.0.just_finished() {
+        combo.break_chain();
+    }
+}
+
+pub fn update_score(
+    mut destruction_events: EventReader<BrickDesctructionEvent>,
+    mut score_events: EventWriter<ScoreIncrementEvent>,
+    mut player_progress: ResMut<PlayerProgress>,
+    mut timer: ResMut<ScoreIncrementTimer>,
+    mut combo: ResMut<ComboChain>,
+    mut remaining: ResMut<RemainingBricks>,
+) {
+    for event in destruction_events.iter() {
+        let chain = combo.hit();
+        let score_increment = brick_score(chain, event.chain_depth);
+        let bonus = score_increment - BASE_BRICK_SCORE;
+
+        score_events.send(ScoreIncrementEvent {
+            amount: score_increment,
+            bonus,
+            position: event.position,
+        });
+
+        let (row_cleared, column_cleared) = remaining.remove(event.cell);
+        let line_bonus = line_clear_bonus(row_cleared, column_cleared);
+
+        if line_bonus > 0. {
+            score_events.send(ScoreIncrementEvent {
+                amount: line_bonus,
+                bonus: line_bonus,
+                position: event.position,
+            });
+        }
+
+        player_progress.score += score_increment + line_bonus;
+        player_progress.bonus_score += bonus + line_bonus;
+        timer.0.reset();
+    }
+}
+
+/// Every regular ball that falls out costs points, even while others are still in play
+fn penalize_ball_loss(
+    mut ball_lost_events: EventReader<BallLostEvent>,
+    mut player_progress: ResMut<PlayerProgress>,
+    mut combo: ResMut<ComboChain>,
+) {
+    // Fireballs burn out on their own, losing one is not the player's fault
+    for _ in ball_lost_events
+        .iter()
+        .filter(|event| event.ball_type == BallType::Regular)
+    {
+        player_progress.score -= ball_lost_penalty(player_progress.score);
+        combo.break_chain();
+    }
+}
+
+pub fn award_level_clear_bonus(
+    mut player_progress: ResMut<PlayerProgress>,
+    clock: Res<LevelClock>,
+) {
+    let bonus = level_clear_bonus(clock.0);
+
+    player_progress.score += bonus;
+    player_progress.bonus_score += bonus;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_hit_scores_base() {
+        assert_eq!(brick_score(1, 0), BASE_BRICK_SCORE);
+    }
+
+    #[test]
+    fn chain_grows_multiplicatively() {
+        assert_eq!(brick_score(2, 0), BASE_BRICK_SCORE * CHAIN_GROWTH);
+        assert_eq!(
+            brick_score(3, 0),
+            BASE_BRICK_SCORE * CHAIN_GROWTH * CHAIN_GROWTH
+        );
+        assert!(brick_score(10, 0) > brick_score(9, 0));
+    }
+
+    #[test]
+    fn chain_multiplier_is_capped() {
+        assert_eq!(
+            brick_score(1000, 0),
+            BASE_BRICK_SCORE * MAX_CHAIN_MULTIPLIER
+        );
+    }
+
+    #[test]
+    fn bomb_depth_adds_multiplier() {
+        assert_eq!(
+            brick_score(1, 2),
+            BASE_BRICK_SCORE * (1. + 2. * BOMB_CHAIN_MULTIPLIER)
+        );
+    }
+
+    #[test]
+    fn chain_tracks_best() {
+        let mut chain = ComboChain::default();
+
+        chain.hit();
+        chain.hit();
+        chain.hit();
+        chain.break_chain();
+        assert_eq!(chain.hit(), 1);
+        assert_eq!(chain.best, 3);
+    }
+
+    #[test]
+    fn removing_last_brick_clears_lines() {
+        let mut level = Level::default();
+        let brick = LevelBrick {
+            color: BrickColor::Red,
+            brick_type: BrickType::Regular,
+            path: None,
+        };
+        level.bricks[0][0] = Some(brick);
+        level.bricks[0][1] = Some(brick);
+
+        let mut remaining = RemainingBricks::from_level(&level);
+
+        assert_eq!(remaining.remove((0, 0)), (false, true));
+        assert_eq!(remaining.remove((1, 0)), (true, true));
+        assert_eq!(
+            line_clear_bonus(true, true),
+            ROW_CLEAR_BONUS + COLUMN_CLEAR_BONUS
+        );
+    }
+
+    #[test]
+    fn regrown_lines_pay_out_once() {
+        let mut level = Level::default();
+        level.bricks[0][0] = Some(LevelBrick {
+            color: BrickColor::Red,
+            brick_type: BrickType::Regular,
+            path: None,
+        });
+
+        let mut remaining = RemainingBricks::from_level(&level);
+
+        assert_eq!(remaining.remove((0, 0)), (true, true));
+        remaining.add((0, 0));
+        assert_eq!(remaining.remove((0, 0)), (false, false));
+    }
+
+    #[test]
+    fn ball_lost_penalty_never_goes_negative() {
+        assert_eq!(ball_lost_penalty(1000.), BALL_LOST_PENALTY);
+        assert_eq!(ball_lost_penalty(100.), 100.);
+        assert_eq!(ball_lost_penalty(0.), 0.);
+    }
+
+    #[test]
+    fn faster_clears_earn_more() {
+        assert!(level_clear_bonus(30.) > level_clear_bonus(60.));
+        assert_eq!(level_clear_bonus(TIME_BONUS_PAR + 10.), 0.);
+    }
+}
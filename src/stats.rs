@@ -0,0 +1,290 @@
+use bevy::ecs::system::SystemParam;
+
+use crate::*;
+
+const TALLY_LINE_DELAY: f32 = 0.35;
+const TALLY_COUNT_DURATION: f32 = 0.5;
+
+pub struct StatsPlugin;
+
+/// What happened during the current level
+#[derive(Resource, Default)]
+pub struct LevelStats {
+    pub bricks_destroyed: u32,
+    pub fireballs_triggered: u32,
+    pub paddle_hits: u32,
+    /// Paddle hits after which the same ball destroyed a brick before returning
+    pub productive_paddle_hits: u32,
+    pub balls_lost: u32,
+    /// Balls that left the paddle and have not destroyed a brick yet
+    awaiting_brick: Vec<Entity>,
+}
+
+impl LevelStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.paddle_hits == 0 {
+            return 1.;
+        }
+
+        self.productive_paddle_hits as f32 / self.paddle_hits as f32
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TallyFormat {
+    Number,
+    Time,
+    Percent,
+}
+
+#[derive(Component)]
+pub struct TallyLine {
+    index: usize,
+    value: f32,
+    format: TallyFormat,
+}
+
+/// Seconds since the tally screen opened
+#[derive(Resource, Default)]
+pub struct TallyProgress(f32);
+
+/// Results of the finished level, counted up on the level complete screen
+#[derive(SystemParam)]
+pub struct TallySource<'w> {
+    stats: Res<'w, LevelStats>,
+    clock: Res<'w, LevelClock>,
+    combo: Res<'w, ComboChain>,
+    player_progress: Res<'w, PlayerProgress>,
+}
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LevelStats::default())
+            .insert_resource(TallyProgress::default());
+
+        app.add_system(reset_level_stats.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(track_level_stats.in_set(OnUpdate(GameState::Playing)));
+
+        // Level completed state, the lines are spawned by the level complete menu screen
+        app.add_system(reset_tally_progress.in_schedule(OnEnter(GameState::LevelCompleted)))
+            .add_systems(
+                (animate_tally_screen, dismiss_tally_screen)
+                    .in_set(OnUpdate(GameState::LevelCompleted)),
+            );
+    }
+}
+
+fn reset_level_stats(mut stats: ResMut<LevelStats>) {
+    *stats = LevelStats::default();
+}
+
+fn track_level_stats(
+    mut stats: ResMut<LevelStats>,
+    mut destruction_events: EventReader<BrickDesctructionEvent>,
+    mut collision_events: EventReader<BallCollisionEvent>,
+    mut ball_lost_events: EventReader<BallLostEvent>,
+) {
+    for event in collision_events.iter() {
+        if event.collision_type == BallCollisionType::Paddle {
+            stats.paddle_hits += 1;
+
+            if !stats.awaiting_brick.contains(&event.ball) {
+                stats.awaiting_brick.push(event.ball);
+            }
+        }
+    }
+
+    for event in destruction_events.iter() {
+        stats.bricks_destroyed += 1;
+
+        if event.brick_type == BrickType::Fireball {
+            stats.fireballs_triggered += 1;
+        }
+
+        if let Some(index) = stats.awaiting_brick.iter().position(|b| *b == event.ball) {
+            stats.awaiting_brick.swap_remove(index);
+            stats.productive_paddle_hits += 1;
+        }
+    }
+
+    // Same balls as the scoring penalty, multiball losses included and fireballs left out
+    stats.balls_lost += ball_lost_events
+        .iter()
+        .filter(|event| event.ball_type == BallType::Regular)
+        .count() as u32;
+}
+
+fn reset_tally_progress(mut progress: ResMut<TallyProgress>) {
+    progress.0 = 0.;
+}
+
+pub fn spawn_tally_lines(
+    parent: &mut ChildBuilder,
+    assets: &GameAssets,
+    locale: &Locale,
+    tally: &TallySource,
+) {
+    let lines = [
+        ("tally_time", tally.clock.0, TallyFormat::Time),
+        (
+            "tally_bricks",
+            tally.stats.bricks_destroyed as f32,
+            TallyFormat::Number,
+        ),
+        (
+            "tally_fireballs",
+            tally.stats.fireballs_triggered as f32,
+            TallyFormat::Number,
+        ),
+        (
+            "tally_best_chain",
+            tally.combo.best as f32,
+            TallyFormat::Number,
+        ),
+        (
+            "tally_paddle_hits",
+            tally.stats.paddle_hits as f32,
+            TallyFormat::Number,
+        ),
+        (
+            "tally_balls_lost",
+            tally.stats.balls_lost as f32,
+            TallyFormat::Number,
+        ),
+        (
+            "tally_accuracy",
+            tally.stats.accuracy(),
+            TallyFormat::Percent,
+        ),
+        (
+            "tally_bonus",
+            tally.player_progress.bonus_score,
+            TallyFormat::Number,
+        ),
+    ];
+
+    for (index, (label, value, format)) in lines.into_iter().enumerate() {
+        let mut line = TextBundle::from_sections([
+            TextSection::new(
+                format!("{}: ", locale.get(label)),
+                TextStyle {
+                    font: assets.font.bold.clone(),
+                    font_size: 34.0,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font: assets.font.mono.clone(),
+                font_size: 34.0,
+                color: Color::GOLD,
+            }),
+        ]);
+        // Hidden lines keep their place so the screen does not shift as they appear
+        line.visibility = Visibility::Hidden;
+
+        if index == lines.len() - 1 {
+            line.style.margin = UiRect::bottom(Val::Px(30.));
+        }
+
+        parent.spawn((
+            line,
+            TallyLine {
+                index,
+                value,
+                format,
+            },
+        ));
+    }
+}
+
+fn format_tally_value(value: f32, format: TallyFormat, locale: &Locale) -> String {
+    match format {
+        TallyFormat::Number => locale.number(value),
+        TallyFormat::Time => locale.duration(value),
+        TallyFormat::Percent => locale.percent(value),
+    }
+}
+
+fn tally_duration(line_count: usize) -> f32 {
+    line_count as f32 * TALLY_LINE_DELAY + TALLY_COUNT_DURATION
+}
+
+fn animate_tally_screen(
+    mut query: Query<(&TallyLine, &mut Text, &mut Visibility)>,
+    mut progress: ResMut<TallyProgress>,
+    locale: Res<Locale>,
+    time: Res<Time>,
+) {
+    progress.0 += time.delta_seconds();
+
+    for (line, mut text, mut visibility) in &mut query {
+        let start = line.index as f32 * TALLY_LINE_DELAY;
+
+        if progress.0 < start {
+            continue;
+        }
+
+        *visibility = Visibility::Visible;
+
+        let counted = ((progress.0 - start) / TALLY_COUNT_DURATION).min(1.);
+        text.sections[1].value = format_tally_value(line.value * counted, line.format, &locale);
+    }
+}
+
+fn dismiss_tally_screen(
+    mut action_events: EventReader<MenuActionEvent>,
+    query: Query<&TallyLine>,
+    playtest: Res<EditorPlaytest>,
+    mut progress: ResMut<TallyProgress>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !action_events
+        .iter()
+        .any(|event| event.0 == MenuAction::NextLevel)
+    {
+        return;
+    }
+
+    let duration = tally_duration(query.iter().count());
+
+    // The first press skips the animation, the next one continues
+    if progress.0 < duration {
+        progress.0 = duration;
+    } else if playtest.0 {
+        *next_state = NextState(Some(GameState::Editor));
+    } else {
+        *next_state = NextState(Some(GameState::Playing));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accuracy_counts_productive_paddle_hits() {
+        let stats = LevelStats {
+            paddle_hits: 4,
+            productive_paddle_hits: 3,
+            ..default()
+        };
+
+        assert_eq!(stats.accuracy(), 0.75);
+        assert_eq!(LevelStats::default().accuracy(), 1.);
+    }
+
+    #[test]
+    fn tally_values_are_formatted() {
+        let locale = Locale::discover();
+
+        assert_eq!(format_tally_value(75., TallyFormat::Time, &locale), "1:15");
+        assert_eq!(
+            format_tally_value(0.5, TallyFormat::Percent, &locale),
+            "50%"
+        );
+        assert_eq!(
+            format_tally_value(1234.4, TallyFormat::Number, &locale),
+            "1,234"
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use std::{fs, path::Path};
+
+/// Directory next to the executable where save files live
+pub const SAVE_DIRECTORY: &str = "saves";
+
+fn save_path(name: &str) -> String {
+    format!("{}/{}", SAVE_DIRECTORY, name)
+}
+
+/// Reads a save file. Fails on platforms without a file system, like the web build
+pub fn read_save(name: &str) -> Result<String, String> {
+    fs::read_to_string(save_path(name)).map_err(|e| e.to_string())
+}
+
+pub fn write_save(name: &str, contents: &str) -> Result<(), String> {
+    let path = save_path(name);
+
+    if let Some(directory) = Path::new(&path).parent() {
+        fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+    }
+
+    fs::write(path, contents).map_err(|e| e.to_string())
+}
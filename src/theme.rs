@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use crate::*;
+
+const THEME_SAVE: &str = "theme";
+const THEME_DIRECTORY: &str = "themes";
+const THEME_EXTENSION: &str = "theme";
+const DEFAULT_THEME: &str = "default";
+
+pub struct ThemePlugin;
+
+/// Logical asset names of a theme mapped to files under the assets folder
+#[derive(Clone, Debug)]
+pub struct ThemeManifest {
+    pub id: String,
+    pub name: String,
+    entries: HashMap<String, String>,
+}
+
+impl ThemeManifest {
+    /// Same `key = value` format as the string tables, `name` is the shown theme name
+    pub fn parse(id: &str, source: &str) -> Self {
+        let mut entries = parse_string_table(source);
+        let name = entries.remove("name").unwrap_or_else(|| id.to_string());
+
+        ThemeManifest {
+            id: id.to_string(),
+            name,
+            entries,
+        }
+    }
+
+    /// Built in so the web build, which cannot list folders, still has it
+    pub fn default_theme() -> Self {
+        ThemeManifest::parse(
+            DEFAULT_THEME,
+            include_str!("../assets/themes/default.theme"),
+        )
+    }
+}
+
+/// State to return to once a newly selected theme has loaded
+#[derive(Resource, Default)]
+pub struct ThemeReload(pub Option<GameState>);
+
+/// Installed themes, the first one is always the default theme
+#[derive(Resource)]
+pub struct Themes {
+    pub installed: Vec<ThemeManifest>,
+    pub current: usize,
+}
+
+impl Themes {
+    /// Default theme plus every manifest found in the themes folder
+    pub fn discover() -> Self {
+        let mut installed = vec![ThemeManifest::default_theme()];
+
+        for (id, source) in read_theme_files() {
+            if id != DEFAULT_THEME {
+                installed.push(ThemeManifest::parse(&id, &source));
+            }
+        }
+
+        installed[1..].sort_by(|a, b| a.id.cmp(&b.id));
+
+        Themes {
+            installed,
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> &ThemeManifest {
+        &self.installed[self.current]
+    }
+
+    pub fn select(&mut self, id: &str) {
+        self.current = self
+            .installed
+            .iter()
+            .position(|theme| theme.id == id.trim())
+            .unwrap_or(0);
+    }
+
+    pub fn next(&mut self) {
+        self.current = (self.current + 1) % self.installed.len();
+    }
+
+    /// File of a logical asset, taken from the default theme if the selected one leaves it out
+    pub fn path(&self, key: &str) -> String {
+        self.current()
+            .entries
+            .get(key)
+            .or_else(|| self.installed[0].entries.get(key))
+            .cloned()
+            .unwrap_or_else(|| {
+                warn!("No theme has an entry for {}", key);
+                key.to_string()
+            })
+    }
+
+    /// Files of a logical asset with variations, listed comma separated
+    pub fn paths(&self, key: &str) -> Vec<String> {
+        self.path(key)
+            .split(',')
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .collect()
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = write_save(THEME_SAVE, &self.current().id) {
+            warn!("Could not save theme: {}", error);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_theme_files() -> Vec<(String, String)> {
+    use std::fs;
+
+    let directory = bevy::asset::FileAssetIo::get_base_path()
+        .join("assets")
+        .join(THEME_DIRECTORY);
+
+    let Ok(entries) = fs::read_dir(directory) else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == THEME_EXTENSION))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_string();
+            let source = fs::read_to_string(&path).ok()?;
+
+            Some((id, source))
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_theme_files() -> Vec<(String, String)> {
+    vec![]
+}
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        let mut themes = Themes::discover();
+
+        if let Ok(id) = read_save(THEME_SAVE) {
+            themes.select(&id);
+        }
+
+        app.insert_resource(themes)
+            .insert_resource(ThemeReload::default())
+            // Themes are picked in the settings menu, never with a game in progress
+            .add_system(
+                reload_theme
+                    .run_if(in_state(GameState::Start).or_else(in_state(GameState::Loading))),
+            )
+            .add_system(fall_back_to_default_theme.in_set(OnUpdate(GameState::Loading)))
+            .add_system(apply_theme_background.run_if(resource_changed::<GameAssets>()));
+    }
+}
+
+/// Swaps in the assets of a newly selected theme and waits for them on the loading screen
+fn reload_theme(
+    themes: Res<Themes>,
+    asset_server: Res<AssetServer>,
+    state: Res<State<GameState>>,
+    mut reload: ResMut<ThemeReload>,
+    mut assets: ResMut<GameAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if themes.is_changed() && !themes.is_added() {
+        // Falling back from a broken theme keeps the state the first reload came from
+        if state.0 != GameState::Loading {
+            reload.0 = Some(state.0.clone());
+        }
+
+        *assets = GameAssets::load(&asset_server, &themes);
+        *next_state = NextState(Some(GameState::Loading));
+    }
+}
+
+/// A broken theme would otherwise keep the game on the error screen for good
+fn fall_back_to_default_theme(
+    key: Res<Input<KeyCode>>,
+    mut themes: ResMut<Themes>,
+    error_query: Query<(), With<LoadingErrorText>>,
+) {
+    if themes.current != 0 && !error_query.is_empty() && key.just_pressed(KeyCode::Escape) {
+        themes.current = 0;
+        themes.save();
+    }
+}
+
+fn apply_theme_background(
+    assets: Res<GameAssets>,
+    mut query: Query<&mut Handle<Image>, With<Background>>,
+) {
+    for mut texture in &mut query {
+        *texture = assets.image.background.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_entries_fall_back_to_the_default_theme() {
+        let mut themes = Themes {
+            installed: vec![
+                ThemeManifest::default_theme(),
+                ThemeManifest::parse("winter", "name = Winter\nball = themes/winter/ball.png"),
+            ],
+            current: 0,
+        };
+        themes.select("winter");
+
+        assert_eq!(themes.current().name, "Winter");
+        assert_eq!(themes.path("ball"), "themes/winter/ball.png");
+        assert_eq!(themes.path("paddle"), "images/paddle.png");
+        assert_eq!(
+            themes.paths("brick_break"),
+            ["sound/drop_004.ogg", "sound/drop_001.ogg"]
+        );
+
+        themes.select("no_such_theme");
+        assert_eq!(themes.current().id, DEFAULT_THEME);
+    }
+}
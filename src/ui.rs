@@ -2,8 +2,12 @@ use bevy::sprite::Anchor;
 
 use crate::*;
 
-const UI_Z_VALUE: f32 = 100.;
+pub const UI_Z_VALUE: f32 = 100.;
 const BG_ANIM_SPEED: f32 = 0.1;
+const POPUP_DURATION: f32 = 0.8;
+const POPUP_RISE_SPEED: f32 = 80.;
+const COMBO_BAR_WIDTH: f32 = 160.;
+const COMBO_BAR_HEIGHT: f32 = 8.;
 
 #[derive(Component)]
 pub struct ScoreText;
@@ -11,11 +15,9 @@ pub struct ScoreText;
 #[derive(Component)]
 pub struct LevelText;
 
+/// Everything shown on top of the playfield while playing
 #[derive(Component)]
-pub struct PlayText;
-
-#[derive(Component)]
-pub struct TitleText;
+pub struct Hud;
 
 #[derive(Component)]
 pub struct UiBall;
@@ -26,6 +28,18 @@ pub struct ScoreValueText;
 #[derive(Component)]
 pub struct Background;
 
+#[derive(Component)]
+pub struct ComboText;
+
+#[derive(Component)]
+pub struct ComboBar;
+
+#[derive(Component)]
+pub struct ScorePopup {
+    age: f32,
+    color: Color,
+}
+
 pub struct UiPlugin;
 
 #[derive(Resource)]
@@ -45,16 +59,13 @@ impl Plugin for UiPlugin {
         // Resources
         app.insert_resource(BackgroundAnimationDirection(true));
 
-        // Start state
-        app.add_systems((spawn_play_text, spawn_title_text).in_schedule(OnEnter(GameState::Start)))
-            .add_system(despawn::<Text>.in_schedule(OnExit(GameState::Start)));
-
         // Playing state
         app.add_systems(
             (
-                spawn_ball_count,
+                spawn_ball_count.after(load_level),
                 spawn_level_text.before(spawn_score_text),
                 spawn_score_text,
+                spawn_combo_meter,
             )
                 .in_schedule(OnEnter(GameState::Playing)),
         )
@@ -64,38 +75,27 @@ impl Plugin for UiPlugin {
                 update_ball_count,
                 update_level_text,
                 update_score_text,
+                spawn_score_popups,
+                animate_score_popups,
+                update_combo_meter.after(update_score),
             )
                 .in_set(OnUpdate(GameState::Playing)),
         )
-        .add_system(despawn::<Text>.in_schedule(OnExit(GameState::Playing)));
+        .add_systems(
+            (despawn::<Hud>, despawn::<ScorePopup>).in_schedule(OnExit(GameState::Playing)),
+        );
 
         // Paused state
         // app.add_system_set(SystemSet::on_enter(GameState::Paused).with_system(spawn_play_text))
         //     .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(despawn::<PlayText>));
-
-        // Level completed state
-        app.add_systems(
-            (spawn_level_complete_text, spawn_bonus_score_text)
-                .in_schedule(OnEnter(GameState::LevelCompleted)),
-        )
-        .add_system(despawn::<Text>.in_schedule(OnExit(GameState::LevelCompleted)));
-
-        // Gameover state
-        app.add_system(spawn_game_over_text.in_schedule(OnEnter(GameState::GameOver)))
-            .add_system(despawn::<Text>.in_schedule(OnExit(GameState::GameOver)));
     }
 }
 
-pub fn spawn_background(mut commands: Commands, assets: Res<GameAssets>) {
+pub fn spawn_background(mut commands: Commands, assets: Res<GameAssets>, palette: Res<Palette>) {
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
-                color: Color::Rgba {
-                    red: 0.2,
-                    green: 0.5,
-                    blue: 0.7,
-                    alpha: 0.9,
-                },
+                color: palette.background_tint(),
                 ..default()
             },
             texture: assets.image.background.clone(),
@@ -125,117 +125,20 @@ pub fn animate_background(
     };
 }
 
-pub fn spawn_title_text(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn(Text2dBundle {
-            text: Text::from_section(
-                "Breakout!",
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 150.0,
-                    color: Color::WHITE,
-                },
-            )
-            .with_alignment(TextAlignment::Center),
-            ..default()
-        })
-        .insert(Transform::from_xyz(0., 120., UI_Z_VALUE))
-        .insert(TitleText);
-}
-
-pub fn spawn_play_text(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn(Text2dBundle {
-            text: Text::from_section(
-                "Click to play",
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                },
-            )
-            .with_alignment(TextAlignment::Center),
-            ..default()
-        })
-        .insert(Transform::from_xyz(0., -200., 1.))
-        .insert(PlayText);
-}
-
-pub fn spawn_game_over_text(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(Text2dBundle {
-        text: Text::from_section(
-            "Game over",
-            TextStyle {
-                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                font_size: 120.0,
-                color: Color::WHITE,
-            },
-        )
-        .with_alignment(TextAlignment::Center),
-        transform: Transform::from_xyz(0., 0., UI_Z_VALUE),
-        ..default()
-    });
-}
-
-pub fn spawn_level_complete_text(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(Text2dBundle {
-        text: Text::from_section(
-            "Level complete",
-            TextStyle {
-                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                font_size: 120.0,
-                color: Color::WHITE,
-            },
-        )
-        .with_alignment(TextAlignment::Center),
-        transform: Transform::from_xyz(0., 0., UI_Z_VALUE),
-        ..default()
-    });
-}
-pub fn spawn_bonus_score_text(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    player_progress: Res<PlayerProgress>,
-) {
-    commands.spawn(Text2dBundle {
-        text: Text::from_sections([
-            TextSection::new(
-                "Bonus: ",
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 60.0,
-                    color: Color::WHITE,
-                },
-            ),
-            TextSection::new(
-                player_progress.bonus_score.round().to_string(),
-                TextStyle {
-                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
-                    font_size: 60.0,
-                    color: Color::GOLD,
-                },
-            ),
-        ])
-        .with_alignment(TextAlignment::Center),
-        transform: Transform::from_xyz(0., -80., UI_Z_VALUE),
-        ..default()
-    });
-}
-
-pub fn spawn_level_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_level_text(mut commands: Commands, assets: Res<GameAssets>, locale: Res<Locale>) {
     commands.spawn((
         Text2dBundle {
             text: Text::from_sections([
                 TextSection::new(
-                    "Level: ",
+                    format!("{} ", locale.get("hud_level")),
                     TextStyle {
-                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font: assets.font.bold.clone(),
                         font_size: 60.0,
                         color: Color::WHITE,
                     },
                 ),
                 TextSection::from_style(TextStyle {
-                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                    font: assets.font.mono.clone(),
                     font_size: 60.0,
                     color: Color::GOLD,
                 }),
@@ -245,16 +148,17 @@ pub fn spawn_level_text(mut commands: Commands, asset_server: Res<AssetServer>)
             ..default()
         },
         LevelText,
+        Hud,
     ));
 }
 
-pub fn spawn_score_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+pub fn spawn_score_text(mut commands: Commands, assets: Res<GameAssets>, locale: Res<Locale>) {
     commands.spawn((
         Text2dBundle {
             text: Text::from_sections([TextSection::new(
-                "Score: ",
+                format!("{} ", locale.get("hud_score")),
                 TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font: assets.font.bold.clone(),
                     font_size: 60.0,
                     color: Color::WHITE,
                 },
@@ -264,12 +168,13 @@ pub fn spawn_score_text(mut commands: Commands, asset_server: Res<AssetServer>)
             ..default()
         },
         ScoreText,
+        Hud,
     ));
 
     commands.spawn((
         Text2dBundle {
             text: Text::from_sections([TextSection::from_style(TextStyle {
-                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font: assets.font.mono.clone(),
                 font_size: 60.0,
                 color: Color::GOLD,
             })]),
@@ -279,6 +184,7 @@ pub fn spawn_score_text(mut commands: Commands, asset_server: Res<AssetServer>)
         },
         ScoreText,
         ScoreValueText,
+        Hud,
     ));
 }
 
@@ -287,6 +193,7 @@ pub fn update_score_text(
     player_progress: Res<PlayerProgress>,
     mut timer: ResMut<ScoreAnimationTimer>,
     mut score_increment_events: EventReader<ScoreIncrementEvent>,
+    locale: Res<Locale>,
     time: Res<Time>,
 ) {
     let mut boost = 0.;
@@ -300,17 +207,17 @@ pub fn update_score_text(
     }
 
     for score_increment in score_increment_events.iter() {
-        if score_increment.0 > 10. {
+        if score_increment.amount > 10. {
             timer.0.reset();
             timer.0.set_duration(Duration::from_secs_f32(
-                SCORE_ANIM_MAX_DURATION * score_increment.0.min(70.) / 70.,
+                SCORE_ANIM_MAX_DURATION * score_increment.amount.min(70.) / 70.,
             ));
         }
     }
 
     for (mut text, mut transform) in &mut query {
         if player_progress.is_changed() {
-            text.sections[0].value = player_progress.score.round().to_string();
+            text.sections[0].value = locale.number(player_progress.score);
         }
 
         if boost > 0. {
@@ -319,6 +226,129 @@ pub fn update_score_text(
     }
 }
 
+pub fn spawn_score_popups(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut score_increment_events: EventReader<ScoreIncrementEvent>,
+    locale: Res<Locale>,
+) {
+    for event in score_increment_events.iter() {
+        // White for plain hits, through gold to red as the bonus takes over
+        let bonus_ratio = (event.bonus / event.amount).clamp(0., 1.);
+        let color = if bonus_ratio < 0.5 {
+            Color::rgb(1., 1. - bonus_ratio * 0.32, 1. - bonus_ratio * 2.)
+        } else {
+            Color::rgb(1., 0.84 - (bonus_ratio - 0.5) * 1.2, 0.)
+        };
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("+{}", locale.number(event.amount)),
+                    TextStyle {
+                        font: assets.font.mono.clone(),
+                        font_size: 24. + 16. * bonus_ratio,
+                        color,
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_xyz(event.position.x, event.position.y, UI_Z_VALUE),
+                ..default()
+            },
+            ScorePopup { age: 0., color },
+        ));
+    }
+}
+
+pub fn animate_score_popups(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut transform, mut text) in &mut query {
+        popup.age += time.delta_seconds();
+
+        if popup.age >= POPUP_DURATION {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+        text.sections[0].style.color = popup.color.with_a(1. - popup.age / POPUP_DURATION);
+    }
+}
+
+pub fn spawn_combo_meter(mut commands: Commands, assets: Res<GameAssets>, locale: Res<Locale>) {
+    let position = Vec3::new(-WIN_WIDTH / 2. + 20., -WIN_HEIGHT / 2. + 20., UI_Z_VALUE);
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    format!("{} ", locale.get("hud_combo")),
+                    TextStyle {
+                        font: assets.font.bold.clone(),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font: assets.font.mono.clone(),
+                    font_size: 30.0,
+                    color: Color::GOLD,
+                }),
+            ]),
+            text_anchor: Anchor::BottomLeft,
+            transform: Transform::from_translation(position + Vec3::Y * COMBO_BAR_HEIGHT),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ComboText,
+        Hud,
+    ));
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::GOLD,
+                custom_size: Some(Vec2::new(COMBO_BAR_WIDTH, COMBO_BAR_HEIGHT)),
+                anchor: Anchor::BottomLeft,
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ComboBar,
+        Hud,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_combo_meter(
+    mut text_query: Query<(&mut Text, &mut Visibility), With<ComboText>>,
+    mut bar_query: Query<(&mut Sprite, &mut Visibility), (With<ComboBar>, Without<ComboText>)>,
+    combo: Res<ComboChain>,
+    timer: Res<ScoreIncrementTimer>,
+) {
+    let visibility = if combo.count > 1 {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    for (mut text, mut text_visibility) in &mut text_query {
+        text.sections[1].value = format!("x{}", combo.count);
+        *text_visibility = visibility;
+    }
+
+    for (mut sprite, mut bar_visibility) in &mut bar_query {
+        let remaining = 1. - timer.0.percent();
+        sprite.custom_size = Some(Vec2::new(COMBO_BAR_WIDTH * remaining, COMBO_BAR_HEIGHT));
+        *bar_visibility = visibility;
+    }
+}
+
 pub fn update_level_text(
     mut query: Query<&mut Text, With<LevelText>>,
     player_progress: Res<PlayerProgress>,
@@ -348,17 +378,21 @@ pub fn spawn_ball_count(
 
 pub fn update_ball_count(
     mut query: Query<&mut Visibility, With<UiBall>>,
-    player_progress: Res<PlayerProgress>,
+    mut life_lost_events: EventReader<LifeLostEvent>,
 ) {
-    let mut i = 1;
+    let Some(lives_remaining) = life_lost_events
+        .iter()
+        .map(|event| event.lives_remaining)
+        .last()
+    else {
+        return;
+    };
 
-    for mut visibility in query.iter_mut() {
-        *visibility = if i <= player_progress.extra_balls_remaining {
+    for (i, mut visibility) in (1..).zip(query.iter_mut()) {
+        *visibility = if i <= lives_remaining {
             Visibility::Visible
         } else {
             Visibility::Hidden
         };
-
-        i += 1;
     }
 }